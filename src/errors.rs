@@ -0,0 +1,75 @@
+// Copyright 2017-2019 JKRB Investments Limited.
+//
+// You should have received a copy of the GNU General Public License
+// along with this file.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Errors reported by token operations
+
+use parity_codec::{Decode, Encode};
+
+/// Reasons a token operation can fail; every message that changes state returns one of
+/// these, so callers learn why a call was refused
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum Error {
+    /// The account does not own the token
+    NotOwner,
+    /// The caller neither owns the token nor is approved to act on it
+    NotApproved,
+    /// The caller does not hold the role the operation requires, such as contract owner,
+    /// minter, bridge, oracle, signer or guardian
+    Unauthorized,
+    /// The token has never been minted or has been burned
+    TokenNotFound,
+    /// The proposal, request, bundle, campaign or other record does not exist
+    NotFound,
+    /// The token is part of a bundle and can only move with it
+    TokenBundled,
+    /// The token cannot move before its unlock block
+    TokenLocked,
+    /// The token is nested inside another token and moves with its parent
+    TokenNested,
+    /// The token is held in custody by the bridge
+    TokenInCustody,
+    /// The token was minted too recently to be transferred
+    TransferCooldown,
+    /// The receiver would hold more tokens than a single account may
+    HolderCapExceeded,
+    /// The mint would exceed the max supply or eat into the reserve
+    SupplyCapExceeded,
+    /// Token movements are halted while the contract is paused
+    Paused,
+    /// The operation is only available while the contract is paused
+    NotPaused,
+    /// With a timelock in place the change has to be scheduled
+    Timelocked,
+    /// The default, all-zero account cannot receive or be approved for tokens
+    ZeroAccount,
+    /// A mint has to create at least one token
+    ZeroAmount,
+    /// A token cannot be transferred to the account already holding it
+    SelfTransfer,
+    /// An owner cannot approve themselves for their own token
+    SelfApproval,
+    /// An argument is out of range or inconsistent with the others
+    InvalidInput,
+    /// The URI is neither an IPFS CID nor uses an allowed scheme
+    InvalidUri,
+    /// The vote, confirmation, score or other entry has already been recorded
+    Duplicate,
+    /// A per-account limit, such as the number of range approvals, has been reached
+    LimitReached,
+    /// The operation has to wait for a later block
+    NotReady,
+    /// The window for the operation has closed
+    Expired,
+    /// The action has already been carried out
+    AlreadyExecuted,
+    /// The caller held no tokens to vote or propose with
+    NoVotingPower,
+    /// Voting closed without a majority in favour or without quorum
+    ProposalRejected,
+    /// A balance or supply counter would exceed its maximum
+    Overflow,
+    /// A balance or supply counter would drop below zero
+    Underflow,
+}
@@ -0,0 +1,121 @@
+// Copyright 2017-2019 JKRB Investments Limited.
+//
+// You should have received a copy of the GNU General Public License
+// along with this file.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Proposals holders can vote on, requests signers confirm and actions the owner
+//! schedules ahead of time, all driving admin actions through the timelock
+
+use crate::types::{is_zero_account, TokenCount};
+use ink_core::{
+    env::{AccountId, BlockNumber, Moment},
+    memory::vec::Vec,
+};
+use parity_codec::{Decode, Encode};
+
+/// Admin actions a passed proposal can carry out
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum Action {
+    /// Set the time a subscription renewal extends a token by
+    SetRenewalPeriod(Moment),
+    /// Set the blocks a fused token waits before it can be fused again
+    SetFusionCooldown(BlockNumber),
+    /// Hand ownership of the contract to another account
+    TransferOwnership(AccountId),
+    /// Set the blocks a scheduled action waits before it can be carried out
+    SetTimelockDelay(BlockNumber),
+    /// Hand the minting role to an account, or back to the owner with None
+    SetMinter(Option<AccountId>),
+    /// Set or clear the account allowed to bridge tokens
+    SetBridge(Option<AccountId>),
+    /// Set how many of the most recent transfers are kept per token
+    SetHistoryLength(u32),
+    /// Halt or resume mints, transfers and burns
+    SetPaused(bool),
+    /// Set the max supply, 0 for none, and the part of it held in reserve
+    SetReserve(TokenCount, TokenCount),
+    /// Set the most tokens an account can hold, 0 for no cap
+    SetMaxPerHolder(TokenCount),
+    /// Set the blocks a freshly minted token waits before it can be transferred
+    SetTransferCooldown(BlockNumber),
+    /// Turn the operator filter on or off
+    SetOperatorFilter(bool),
+    /// Register or remove an operator with the operator filter
+    RegisterOperator(AccountId, bool),
+    /// Set the oracle account and how often it may update a token
+    SetOracle(Option<AccountId>, BlockNumber),
+    /// Set the URI schemes accepted for asset URIs besides `ipfs://<CID>`
+    SetAllowedUriSchemes(Vec<Vec<u8>>),
+    /// Set how long proposals stay open and the quorum, in basis points, they need
+    SetGovernance(BlockNumber, u32),
+    /// Set the accounts that confirm admin requests and how many of them have to agree
+    SetSigners(Vec<AccountId>, u32),
+    /// Set, or remove with None, the account able to move tokens by force while paused
+    SetSuperAdmin(Option<AccountId>),
+}
+
+impl Action {
+    /// Whether holders can put the action to a vote: pausing, the accepted URI schemes and the
+    /// operator filter that keeps trading on royalty-honouring venues. Roles, supply and the
+    /// governance settings themselves stay with the owner and the signers
+    pub fn is_proposable(&self) -> bool {
+        match self {
+            Action::SetPaused(_)
+            | Action::SetAllowedUriSchemes(_)
+            | Action::SetOperatorFilter(_)
+            | Action::RegisterOperator(_, _) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A proposal and its running tally
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct Proposal {
+    /// Action scheduled if the proposal passes
+    pub action: Action,
+    /// Snapshot whose voting power weighs the votes
+    pub snapshot_id: u64,
+    /// Last block votes are accepted in
+    pub end_block: BlockNumber,
+    /// Votes in favour
+    pub votes_for: u64,
+    /// Votes against
+    pub votes_against: u64,
+    /// Whether the action has been handed to the timelock
+    pub executed: bool,
+}
+
+/// An admin action awaiting confirmation by the contract's signers
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct AdminRequest {
+    /// Action scheduled once enough signers confirm
+    pub action: Action,
+    /// Whether the action has been handed to the timelock
+    pub executed: bool,
+}
+
+/// An admin action scheduled by the owner, or passed by holders or signers,
+/// held back until its block
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ScheduledAction {
+    /// Action carried out once the delay has passed
+    pub action: Action,
+    /// First block the action can be carried out in
+    pub eta: BlockNumber,
+    /// Whether the owner can cancel it; only actions the owner scheduled can be
+    pub cancellable: bool,
+}
+
+/// Whether a quorum, in basis points, is at most the whole supply
+pub(crate) fn is_valid_quorum(quorum_bps: u32) -> bool {
+    quorum_bps <= 10_000
+}
+
+/// Whether a signer set lists distinct, non-zero accounts and a threshold they can reach
+pub(crate) fn is_valid_signers(signers: &[AccountId], threshold: u32) -> bool {
+    if threshold == 0 || threshold as usize > signers.len() {
+        return false;
+    }
+    signers.iter().enumerate().all(|(i, signer)| !is_zero_account(signer) && !signers[..i].contains(signer))
+}
@@ -0,0 +1,40 @@
+// Copyright 2017-2019 JKRB Investments Limited.
+//
+// You should have received a copy of the GNU General Public License
+// along with this file.  If not, see <http://www.gnu.org/licenses/>.
+
+//! BLAKE2b hashing for values computed on-chain
+
+use blake2_rfc::blake2b::blake2b;
+use ink_core::env::Hash;
+use parity_codec::Encode;
+
+/// The 256-bit BLAKE2b hash of some bytes
+pub(crate) fn blake2_256(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(blake2b(32, &[], data).as_bytes());
+    hash
+}
+
+/// The hash of a value's SCALE encoding, so off-chain code can recompute it
+pub(crate) fn hash_of<T: Encode>(value: &T) -> Hash {
+    Hash::from(blake2_256(&value.encode()))
+}
+
+/// The parent of two Merkle tree nodes: the hash of the pair, lower node first, so proofs
+/// need not say which side each sibling is on
+pub(crate) fn merkle_parent(a: &Hash, b: &Hash) -> Hash {
+    let (a, b): (&[u8], &[u8]) = (a.as_ref(), b.as_ref());
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let mut pair = [0u8; 64];
+    pair[..32].copy_from_slice(low);
+    pair[32..].copy_from_slice(high);
+    Hash::from(blake2_256(&pair))
+}
+
+/// Whether a leaf belongs to the Merkle tree with the given root, walking up the proof's
+/// siblings from the leaf
+pub(crate) fn verify_merkle_proof(leaf: Hash, proof: &[Hash], root: Hash) -> bool {
+    let node = proof.iter().fold(leaf, |node, sibling| merkle_parent(&node, sibling));
+    node == root
+}
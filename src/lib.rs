@@ -5,43 +5,416 @@
 
 #![cfg_attr(not(any(test, feature = "std")), no_std)]
 
-use ink_core::{env::AccountId, storage};
+use ink_core::{
+    env::{AccountId, BlockNumber, Hash, Moment},
+    memory::vec::Vec,
+    storage,
+};
 use ink_lang::contract;
 
+mod errors;
+mod governance;
+mod hashing;
+mod recovery;
+mod relay;
+mod signatures;
+#[cfg(feature = "std")]
+pub mod testing;
+mod types;
+mod uri;
+
+pub use errors::Error;
+pub use governance::{Action, AdminRequest, Proposal, ScheduledAction};
+pub use recovery::{GuardianSet, RecoveryRequest};
+pub use relay::RelayedCall;
+pub use types::TokenCount;
+use governance::{is_valid_quorum, is_valid_signers};
+use hashing::{blake2_256, hash_of, verify_merkle_proof};
+use parity_codec::Encode;
+use types::{is_zero_account, push_checkpoint, snapshot_value};
+use uri::{has_scheme, is_ipfs_uri, is_valid_schemes};
+
+/// Most recipients a single airdrop call mints to
+const MAX_AIRDROP_BATCH: u32 = 100;
+/// Most range approvals a single owner can have at once
+const MAX_RANGE_APPROVALS: usize = 16;
+/// Transfers kept per token until the owner configures otherwise
+const DEFAULT_HISTORY_LENGTH: u32 = 10;
+/// Name and version hashed into the signing domain
+const DOMAIN_NAME: &[u8] = b"NFToken";
+const DOMAIN_VERSION: u32 = 1;
+
+/// Runs the body of a message and, with the `diagnostics` feature on, emits an
+/// EventDiagnostic naming the message and the error whenever it fails. The event carries
+/// the message name rather than its selector: contract! computes selectors in its own
+/// codegen and exposes none of them to message bodies, while the name maps to exactly one
+/// selector in the generated API description
+macro_rules! diagnosed {
+    ($env:ident, $message:ident, $body:block) => {{
+        // the body runs as a closure so its early returns and `?` yield a single result
+        let result = (|| -> Result<_, Error> $body)();
+        if cfg!(feature = "diagnostics") {
+            if let Err(error) = result {
+                $env.emit(EventDiagnostic { caller: $env.caller(), message: stringify!($message).as_bytes().to_vec(), error: error });
+            }
+        }
+        result
+    }};
+}
+
 contract! {
 
     /// Storage values of the contract
     struct NFToken {
         /// Owner of contract
         owner: storage::Value<AccountId>,
+        /// Account allowed to mint in place of the owner, such as a governance executor
+        minter: storage::Value<Option<AccountId>>,
+        /// Mapping: batch start token_id(u64) -> (authorized_by(AccountId), proposal_id(u64))
+        mint_provenance: storage::HashMap<u64, (AccountId, u64)>,
+        /// Whether mints, transfers and burns are halted
+        paused: storage::Value<bool>,
+        /// Account allowed to move tokens by force while the contract is paused, if one is set
+        super_admin: storage::Value<Option<AccountId>>,
         /// Total tokens minted
-        total_minted: storage::Value<u64>,
-        /// Mapping: token_id(u64) -> owner (AccountID)
+        total_minted: storage::Value<TokenCount>,
+        /// Most tokens that can ever be minted, reserve included; 0 for no limit
+        max_supply: storage::Value<TokenCount>,
+        /// Tokens held back for mint_reserved and not yet minted
+        reserved_remaining: storage::Value<TokenCount>,
+        /// Total tokens burned
+        total_burned: storage::Value<TokenCount>,
+        /// Mapping: token_id(u64) -> owner (AccountID), set wherever a token has changed
+        /// hands since its mint
         id_to_owner: storage::HashMap<u64, AccountId>,
-        /// Mapping: owner(AccountID) -> tokenCount (u64)
-        owner_to_token_count: storage::HashMap<AccountId, u64>,
+        /// Mapping: first token_id(u64) of a mint batch -> (last token_id(u64), receiver(AccountId),
+        /// index(TokenCount) of its first token in the receiver's enumeration)
+        mint_batches: storage::HashMap<u64, (u64, AccountId, TokenCount)>,
+        /// Total mint batches ever recorded
+        total_batches: storage::Value<u64>,
+        /// Mapping: batch index(u64) -> first token_id(u64) of the batch, in minting order
+        batch_starts: storage::HashMap<u64, u64>,
+        /// Mapping: token_id(u64) -> burned(bool)
+        burned: storage::HashMap<u64, bool>,
+        /// Most recent transfers kept per token
+        history_length: storage::Value<u32>,
+        /// Mapping: token_id(u64) -> (from(AccountId), to(AccountId), block(BlockNumber)) transfers, oldest first
+        transfer_history: storage::HashMap<u64, Vec<(AccountId, AccountId, BlockNumber)>>,
+        /// Mapping: token_id(u64) -> times the token changed hands(u32)
+        transfer_counts: storage::HashMap<u64, u32>,
+        /// Mapping: batch start token_id(u64) -> (minter(AccountId), mint block(BlockNumber))
+        mint_origins: storage::HashMap<u64, (AccountId, BlockNumber)>,
+        /// Accounts currently holding at least one token
+        holder_count: storage::Value<u64>,
+        /// Mapping: token_id(u64) -> rarity score(u32), fixed once committed
+        rarity_scores: storage::HashMap<u64, u32>,
+        /// Merkle root of the (token_id(u64), score(u32)) rarity leaves, fixed once committed
+        rarity_root: storage::Value<Option<Hash>>,
+        /// URI schemes accepted for asset URIs besides `ipfs://<CID>`, such as `ar`
+        allowed_uri_schemes: storage::Value<Vec<Vec<u8>>>,
+        /// Account allowed to push attribute updates for dynamic tokens, if one is configured
+        oracle: storage::Value<Option<AccountId>>,
+        /// Blocks the oracle waits between two updates of the same token
+        oracle_interval: storage::Value<BlockNumber>,
+        /// Mapping: token_id(u64) -> dynamic(bool), whether the oracle may update the token
+        dynamic_tokens: storage::HashMap<u64, bool>,
+        /// Mapping: token_id(u64) -> block(BlockNumber) of the last oracle update
+        oracle_updated_at: storage::HashMap<u64, BlockNumber>,
+        /// Mapping: (token_id(u64), key(Vec<u8>)) -> value(Vec<u8>) of dynamic attributes
+        attributes: storage::HashMap<(u64, Vec<u8>), Vec<u8>>,
+        /// Mapping: holder(AccountId) -> guardians(GuardianSet)
+        guardians: storage::HashMap<AccountId, GuardianSet>,
+        /// Mapping: holder(AccountId) -> recovery(RecoveryRequest) in progress
+        recoveries: storage::HashMap<AccountId, RecoveryRequest>,
+        /// Mapping: holder(AccountId) -> (backup(AccountId), delay(BlockNumber))
+        backups: storage::HashMap<AccountId, (AccountId, BlockNumber)>,
+        /// Mapping: holder(AccountId) -> block(BlockNumber) from which their backup can sweep their tokens
+        backup_ready_at: storage::HashMap<AccountId, BlockNumber>,
+        /// Blocks a freshly minted token has to wait before its first transfer
+        transfer_cooldown: storage::Value<BlockNumber>,
+        /// Most tokens a single account may hold; 0 for no limit
+        max_per_holder: storage::Value<TokenCount>,
+        /// Whether operators have to be registered to be approved for all of a holder's tokens
+        operator_filter: storage::Value<bool>,
+        /// Mapping: operator(AccountId) -> registered(bool) with the operator filter
+        registered_operators: storage::HashMap<AccountId, bool>,
+        /// Mapping: (owner(AccountId), operator(AccountId)) -> transfers(u32) the operator may still make
+        operator_allowances: storage::HashMap<(AccountId, AccountId), u32>,
+        /// Mapping: owner(AccountId) -> (spender(AccountId), from_id(u64), to_id(u64)) approved ranges
+        range_approvals: storage::HashMap<AccountId, Vec<(AccountId, u64, u64)>>,
+        /// Mapping: account(AccountId) -> next nonce(u64) its signed authorizations have to carry
+        nonces: storage::HashMap<AccountId, u64>,
+        /// Mapping: owner(AccountID) -> tokenCount (TokenCount)
+        owner_to_token_count: storage::HashMap<AccountId, TokenCount>,
+        /// Mapping: (owner(AccountId), index(TokenCount)) -> token_id(u64) of tokens placed in the
+        /// enumeration one at a time; other indexes resolve through the owner's owned_segments
+        owned_tokens: storage::HashMap<(AccountId, TokenCount), u64>,
+        /// Mapping: token_id(u64) -> index(TokenCount) in its owner's enumeration, for tokens placed
+        /// one at a time; other tokens sit where their mint batch was laid out
+        owned_index: storage::HashMap<u64, TokenCount>,
+        /// Mapping: (owner(AccountId), segment(u64)) -> (first token_id(u64), index(TokenCount)) of a
+        /// mint batch laid out in the owner's enumeration from that index on
+        owned_segments: storage::HashMap<(AccountId, u64), (u64, TokenCount)>,
+        /// Mapping: owner(AccountId) -> live segments(u64) in owned_segments, by increasing index
+        owned_segment_count: storage::HashMap<AccountId, u64>,
+        /// Id of the latest ownership snapshot, 0 before the first one
+        current_snapshot: storage::Value<u64>,
+        /// Mapping: (owner(AccountId), checkpoint(u32)) -> (snapshot_id(u64), balance(TokenCount)),
+        /// each holding the balance the owner had when that snapshot was taken, by increasing snapshot_id
+        balance_snapshots: storage::HashMap<(AccountId, u32), (u64, TokenCount)>,
+        /// Mapping: owner(AccountId) -> checkpoints(u32) in balance_snapshots
+        balance_snapshot_count: storage::HashMap<AccountId, u32>,
+        /// Mapping: checkpoint(u64) -> (snapshot_id(u64), supply(TokenCount)) of the circulating
+        /// supply, by increasing snapshot_id
+        supply_snapshots: storage::HashMap<u64, (u64, TokenCount)>,
+        /// Total checkpoints in supply_snapshots
+        total_supply_snapshots: storage::Value<u64>,
+        /// Mapping: holder(AccountId) -> delegate(AccountId); holders without an entry vote themselves
+        delegates: storage::HashMap<AccountId, AccountId>,
+        /// Mapping: account(AccountId) -> votes(TokenCount) delegated to it
+        voting_power: storage::HashMap<AccountId, TokenCount>,
+        /// Mapping: (account(AccountId), checkpoint(u32)) -> (snapshot_id(u64), votes(TokenCount)),
+        /// each holding the votes delegated to the account when that snapshot was taken, by
+        /// increasing snapshot_id
+        voting_power_snapshots: storage::HashMap<(AccountId, u32), (u64, TokenCount)>,
+        /// Mapping: account(AccountId) -> checkpoints(u32) in voting_power_snapshots
+        voting_power_snapshot_count: storage::HashMap<AccountId, u32>,
+        /// Total proposals ever created
+        total_proposals: storage::Value<u64>,
+        /// Mapping: proposal_id(u64) -> proposal(Proposal)
+        proposals: storage::HashMap<u64, Proposal>,
+        /// Mapping: (proposal_id(u64), voter(AccountId)) -> support(bool)
+        proposal_votes: storage::HashMap<(u64, AccountId), bool>,
+        /// Blocks a proposal stays open for voting
+        voting_period: storage::Value<BlockNumber>,
+        /// Share of the snapshot supply, in basis points, that has to vote for a proposal to pass
+        quorum_bps: storage::Value<u32>,
+        /// Accounts that can submit and confirm admin requests
+        signers: storage::Value<Vec<AccountId>>,
+        /// Confirmations an admin request needs before its action is scheduled
+        signer_threshold: storage::Value<u32>,
+        /// Total admin requests ever submitted
+        total_admin_requests: storage::Value<u64>,
+        /// Mapping: request_id(u64) -> request(AdminRequest)
+        admin_requests: storage::HashMap<u64, AdminRequest>,
+        /// Mapping: (request_id(u64), signer(AccountId)) -> confirmed(bool)
+        admin_confirmations: storage::HashMap<(u64, AccountId), bool>,
+        /// Blocks a scheduled admin action waits before it can be carried out; while
+        /// non-zero, the owner's admin setters have to go through the timelock
+        timelock_delay: storage::Value<BlockNumber>,
+        /// Total admin actions ever scheduled
+        total_scheduled: storage::Value<u64>,
+        /// Mapping: schedule_id(u64) -> scheduled action(ScheduledAction) still pending
+        scheduled: storage::HashMap<u64, ScheduledAction>,
+        /// Account allowed to move tokens across chains, if one is configured
+        bridge: storage::Value<Option<AccountId>>,
+        /// Mapping: token_id(u64) -> bridged out(bool) and awaiting its return
+        bridged_out: storage::HashMap<u64, bool>,
+        /// Mapping: token_id(u64) -> (owner(AccountId), dest_chain(u32), dest_account(Vec<u8>))
+        /// of tokens held in bridge custody
+        bridge_locks: storage::HashMap<u64, (AccountId, u32, Vec<u8>)>,
         /// Mapping: token_id(u64) to account(AccountId)
         approvals: storage::HashMap<u64, AccountId>,
+        /// Mapping: owner(AccountId) -> token_ids(Vec<u64>) that carry an approval
+        owner_approvals: storage::HashMap<AccountId, Vec<u64>>,
+        /// Mapping: (owner(AccountId), operator(AccountId)) -> approved for all tokens(bool)
+        operator_approvals: storage::HashMap<(AccountId, AccountId), bool>,
+        /// Total bundles ever created
+        total_bundles: storage::Value<u64>,
+        /// Mapping: bundle_id(u64) -> token_ids(Vec<u64>)
+        bundles: storage::HashMap<u64, Vec<u64>>,
+        /// Mapping: token_id(u64) -> bundle_id(u64)
+        token_to_bundle: storage::HashMap<u64, u64>,
+        /// Mapping: token_id(u64) -> (user(AccountId), expires(BlockNumber))
+        users: storage::HashMap<u64, (AccountId, BlockNumber)>,
+        /// Mapping: token_id(u64) -> expires_at(Moment)
+        expirations: storage::HashMap<u64, Moment>,
+        /// Time a renewal extends a token by
+        renewal_period: storage::Value<Moment>,
+        /// Mapping: first token_id(u64) of a mint batch -> unlock_at(BlockNumber)
+        unlock_blocks: storage::HashMap<u64, BlockNumber>,
+        /// Mapping: child token_id(u64) -> parent token_id(u64)
+        parents: storage::HashMap<u64, u64>,
+        /// Mapping: parent token_id(u64) -> child token_ids(Vec<u64>)
+        children: storage::HashMap<u64, Vec<u64>>,
+        /// Mapping: (slot(Vec<u8>), token_id(u64)) -> whether the token fits the slot
+        slot_rules: storage::HashMap<(Vec<u8>, u64), bool>,
+        /// Mapping: (parent token_id(u64), slot(Vec<u8>)) -> equipped child token_id(u64)
+        equipped: storage::HashMap<(u64, Vec<u8>), u64>,
+        /// Mapping: child token_id(u64) -> slot(Vec<u8>) it is equipped in
+        equipped_slots: storage::HashMap<u64, Vec<u8>>,
+        /// Mapping: token_id(u64) -> accepted assets(Vec<(priority(u32), uri(Vec<u8>))>)
+        assets: storage::HashMap<u64, Vec<(u32, Vec<u8>)>>,
+        /// Mapping: token_id(u64) -> proposed assets(Vec<(priority(u32), uri(Vec<u8>))>)
+        pending_assets: storage::HashMap<u64, Vec<(u32, Vec<u8>)>>,
+        /// Mapping: token_id(u64) -> genes(u64) deciding its attributes
+        genes: storage::HashMap<u64, u64>,
+        /// Mapping: token_id(u64) -> block(BlockNumber) it can be fused from
+        fusion_ready_at: storage::HashMap<u64, BlockNumber>,
+        /// Blocks a freshly fused token has to wait before it can be fused again
+        fusion_cooldown: storage::Value<BlockNumber>,
+        /// Total redemption campaigns ever created
+        total_campaigns: storage::Value<u64>,
+        /// Mapping: campaign_id(u64) -> (burns token(bool), open(bool))
+        campaigns: storage::HashMap<u64, (bool, bool)>,
+        /// Mapping: (campaign_id(u64), token_id(u64)) -> claim hash(Hash) of the redemption
+        redemptions: storage::HashMap<(u64, u64), Hash>,
+        /// Mapping: handle(Vec<u8>) -> token_id(u64)
+        handles: storage::HashMap<Vec<u8>, u64>,
+        /// Mapping: token_id(u64) -> handle(Vec<u8>)
+        token_handles: storage::HashMap<u64, Vec<u8>>,
+        /// Merkle root of the (index(u64), account(AccountId), quantity(TokenCount)) claim leaves,
+        /// with the minter who published it
+        claim_root: storage::Value<Option<(Hash, AccountId)>>,
+        /// Claim round of the current root; each new root starts a round with nothing claimed
+        claim_round: storage::Value<u64>,
+        /// Mapping: (round(u64), word(u64)) -> bitmap(u64) of the claimed leaf indices,
+        /// leaf index lives at bit index % 64 of word index / 64
+        claimed: storage::HashMap<(u64, u64), u64>,
+        /// Mapping: claim hash(Hash) -> (sender(AccountId), token_id(u64)) of a gift in escrow
+        gifts: storage::HashMap<Hash, (AccountId, u64)>,
+        /// Mapping: token_id(u64) -> claim hash(Hash) the token is escrowed against
+        gift_hashes: storage::HashMap<u64, Hash>,
+        /// Mapping: commitment(Hash) -> block(BlockNumber) a claimer committed to hash(secret, claimer) at
+        gift_commitments: storage::HashMap<Hash, BlockNumber>,
+        /// Hash of the signing domain, bound into every payload signed for this deployment
+        domain_separator: storage::Value<Hash>,
     }
 
     /// compulsary Demploy method
     impl Deploy for NFToken {
         /// Initializes our initial total minted value to 0.
-        fn deploy(&mut self, init_value: u64) {
+        fn deploy(&mut self, init_value: TokenCount) {
+            self.minter.set(None);
+            self.paused.set(false);
+            self.super_admin.set(None);
+            self.max_supply.set(0);
+            self.reserved_remaining.set(0);
             self.total_minted.set(0);
+            self.total_batches.set(0);
+            self.total_burned.set(0);
+            self.history_length.set(DEFAULT_HISTORY_LENGTH);
+            self.holder_count.set(0);
+            self.allowed_uri_schemes.set(Vec::new());
+            self.oracle.set(None);
+            self.oracle_interval.set(0);
+            self.transfer_cooldown.set(0);
+            self.max_per_holder.set(0);
+            self.operator_filter.set(false);
+            self.current_snapshot.set(0);
+            self.total_supply_snapshots.set(0);
+            self.total_proposals.set(0);
+            self.voting_period.set(0);
+            self.quorum_bps.set(0);
+            self.signers.set(Vec::new());
+            self.signer_threshold.set(0);
+            self.total_admin_requests.set(0);
+            self.timelock_delay.set(0);
+            self.total_scheduled.set(0);
+            self.bridge.set(None);
+            self.total_bundles.set(0);
+            self.renewal_period.set(0);
+            self.fusion_cooldown.set(0);
+            self.total_campaigns.set(0);
+            self.claim_root.set(None);
+            self.rarity_root.set(None);
+            self.claim_round.set(0);
+            // contracts have no access to the genesis hash, so the domain is bound to the
+            // contract's address and version only
+            self.domain_separator.set(hash_of(&(DOMAIN_NAME, DOMAIN_VERSION, env.address())));
             // set ownership of contract
             self.owner.set(env.caller());
             // mint initial tokens
             if init_value > 0 {
-              self.mint_impl(env.caller(), init_value);
+              self.mint_impl(env.caller(), env.caller(), init_value, env.block_number());
+              env.emit(EventConsecutiveTransfer { from_id: 1, to_id: init_value, owner: env.caller() });
             }
         }
     }
 
     /// Events
-    event EventMint { owner: AccountId, value: u64 }
+    event EventMint { owner: AccountId, value: TokenCount }
+    event EventMinterSet { caller: AccountId, minter: Option<AccountId> }
+    event EventMintAuthorized { minter: AccountId, from_id: u64, to_id: u64, proposal_id: u64 }
+    event EventReserveSet { caller: AccountId, max_supply: TokenCount, reserved: TokenCount }
+    event EventPaused { caller: AccountId, paused: bool }
+    event EventSuperAdminSet { caller: AccountId, super_admin: Option<AccountId> }
+    event EventAdminTransfer { admin: AccountId, from: AccountId, to: AccountId, token_id: u64, reason_hash: Hash, block: BlockNumber }
+    event EventConsecutiveTransfer { from_id: u64, to_id: u64, owner: AccountId }
+    event EventDelegateChanged { delegator: AccountId, from_delegate: AccountId, to_delegate: AccountId }
+    event EventGovernanceSet { caller: AccountId, voting_period: BlockNumber, quorum_bps: u32 }
+    event EventProposalCreated { proposer: AccountId, proposal_id: u64, snapshot_id: u64, end_block: BlockNumber }
+    event EventVoteCast { voter: AccountId, proposal_id: u64, support: bool, votes: TokenCount }
+    event EventProposalExecuted { caller: AccountId, proposal_id: u64 }
+    event EventSignersSet { caller: AccountId, signers: Vec<AccountId>, threshold: u32 }
+    event EventAdminRequestSubmitted { signer: AccountId, request_id: u64 }
+    event EventAdminRequestConfirmed { signer: AccountId, request_id: u64 }
+    event EventAdminRequestExecuted { request_id: u64 }
+    event EventActionScheduled { caller: AccountId, schedule_id: u64, eta: BlockNumber }
+    event EventScheduledExecuted { caller: AccountId, schedule_id: u64 }
+    event EventScheduledCancelled { caller: AccountId, schedule_id: u64 }
+    event EventTimelockDelaySet { caller: AccountId, delay: BlockNumber }
+    event EventBridgeSet { caller: AccountId, bridge: Option<AccountId> }
+    event EventBridgeOut { owner: AccountId, token_id: u64, dest_chain: u32, dest_account: Vec<u8> }
+    event EventBridgeIn { owner: AccountId, token_id: u64, proof: Vec<u8> }
+    event EventBridgeLock { owner: AccountId, token_id: u64, dest_chain: u32, dest_account: Vec<u8> }
+    event EventBridgeUnlock { owner: AccountId, token_id: u64 }
+    event EventHistoryLengthSet { caller: AccountId, length: u32 }
+    event EventRarityCommitted { caller: AccountId, token_ids: Vec<u64> }
+    event EventRarityRootCommitted { caller: AccountId, root: Hash }
+    event EventOracleSet { caller: AccountId, oracle: Option<AccountId>, interval: BlockNumber }
+    event EventDynamicSet { caller: AccountId, token_id: u64, dynamic: bool }
+    event EventAttributesUpdated { token_id: u64, keys: Vec<Vec<u8>> }
+    event EventGuardiansSet { owner: AccountId, threshold: u32, delay: BlockNumber }
+    event EventRecoverySupported { guardian: AccountId, owner: AccountId, to: AccountId }
+    event EventRecoveryStarted { owner: AccountId, to: AccountId, ready_at: BlockNumber }
+    event EventRecoveryVetoed { owner: AccountId }
+    event EventRecovered { owner: AccountId, to: AccountId, tokens: u32 }
+    event EventBackupSet { owner: AccountId, backup: Option<AccountId>, delay: BlockNumber }
+    event EventBackupAnnounced { owner: AccountId, backup: AccountId, ready_at: BlockNumber }
+    event EventBackupCancelled { owner: AccountId }
+    event EventTransferCooldownSet { caller: AccountId, cooldown: BlockNumber }
+    event EventMaxPerHolderSet { caller: AccountId, max_per_holder: TokenCount }
+    event EventOperatorFilterSet { caller: AccountId, enabled: bool }
+    event EventOperatorRegistered { caller: AccountId, operator: AccountId, registered: bool }
+    event EventOperatorAllowance { owner: AccountId, operator: AccountId, transfers: u32 }
+    event EventRangeApproval { owner: AccountId, spender: AccountId, from_id: u64, to_id: u64, approved: bool }
+    event EventRelayed { relayer: AccountId, signer: AccountId, nonce: u64 }
+    event EventNonceInvalidated { account: AccountId, nonce: u64 }
+    event EventSnapshot { caller: AccountId, snapshot_id: u64 }
+    event EventOwnershipTransferred { previous_owner: AccountId, new_owner: AccountId }
     event EventTransfer { from: AccountId, to: AccountId, token_id: u64 }
+    event EventBurn { owner: AccountId, token_id: u64 }
     event EventApproval { owner: AccountId, spender: AccountId, token_id: u64, approved: bool }
+    event EventApprovalForAll { owner: AccountId, operator: AccountId, approved: bool }
+    event EventBundleCreated { owner: AccountId, bundle_id: u64 }
+    event EventBundleTransfer { from: AccountId, to: AccountId, bundle_id: u64 }
+    event EventBundleDissolved { owner: AccountId, bundle_id: u64 }
+    event EventUpdateUser { token_id: u64, user: AccountId, expires: BlockNumber }
+    event EventExpiryUpdated { caller: AccountId, token_id: u64, expires_at: Moment }
+    event EventRenewalPeriodSet { caller: AccountId, period: Moment }
+    event EventNest { owner: AccountId, parent_id: u64, child_id: u64 }
+    event EventEject { owner: AccountId, parent_id: u64, child_id: u64 }
+    event EventSlotRule { caller: AccountId, slot: Vec<u8>, token_id: u64, allowed: bool }
+    event EventEquip { parent_id: u64, slot: Vec<u8>, child_id: u64 }
+    event EventUnequip { parent_id: u64, slot: Vec<u8>, child_id: u64 }
+    event EventUriSchemesSet { caller: AccountId, schemes: Vec<Vec<u8>> }
+    event EventAssetProposed { token_id: u64, priority: u32, uri: Vec<u8> }
+    event EventAssetAccepted { token_id: u64, priority: u32, uri: Vec<u8> }
+    event EventAssetRejected { token_id: u64, uri: Vec<u8> }
+    event EventCampaignCreated { caller: AccountId, campaign_id: u64, burns: bool }
+    event EventCampaignClosed { caller: AccountId, campaign_id: u64 }
+    event EventRedeemed { owner: AccountId, token_id: u64, campaign_id: u64, claim_hash: Hash }
+    event EventHandleSet { token_id: u64, handle: Vec<u8> }
+    event EventFusionCooldownSet { caller: AccountId, cooldown: BlockNumber }
+    event EventFuse { owner: AccountId, token_a: u64, token_b: u64, child_id: u64, genes: u64 }
+    event EventClaimRootSet { caller: AccountId, round: u64, root: Option<Hash> }
+    event EventClaimed { owner: AccountId, index: u64, quantity: TokenCount }
+    event EventGiftCreated { sender: AccountId, token_id: u64, claim_hash: Hash }
+    event EventGiftClaimCommitted { claimer: AccountId, commitment: Hash }
+    event EventGiftClaimed { sender: AccountId, recipient: AccountId, token_id: u64 }
+    event EventGiftCancelled { sender: AccountId, token_id: u64 }
+    event EventDiagnostic { caller: AccountId, message: Vec<u8>, error: Error }
 
     /// Public methods
     impl NFToken {
@@ -58,215 +431,5047 @@ contract! {
             false
         }
 
+        /// Return the account approved to send a token, if any
+        pub(external) fn approval_of(&self, token_id: u64) -> Option<AccountId> {
+            self.approvals.get(&token_id).cloned()
+        }
+
+        /// Return the owner of the contract
+        pub(external) fn owner(&self) -> AccountId {
+            let owner = *self.owner;
+            owner
+        }
+
         /// Return the total amount of tokens ever minted
-        pub(external) fn total_minted(&self) -> u64 {
+        pub(external) fn total_minted(&self) -> TokenCount {
             let total_minted = *self.total_minted;
             total_minted
         }
 
-        /// Return the balance of the given address.
-        pub(external) fn balance_of(&self, owner: AccountId) -> u64 {
-            let balance = *self.owner_to_token_count.get(&owner).unwrap_or(&0);
-            balance
+        /// Return the total amount of tokens ever burned
+        pub(external) fn total_burned(&self) -> TokenCount {
+            let total_burned = *self.total_burned;
+            total_burned
         }
 
-        /// Transfers a token_id to a specified address from the caller
-        pub(external) fn transfer(&mut self, to: AccountId, token_id: u64) -> bool {
-            // carry out the actual transfer
-            if self.transfer_impl(env.caller(), to, token_id) == true {
-                env.emit(EventTransfer { from: env.caller(), to: to, token_id: token_id });
-                return true;
-            }
-            false
+        /// Return the owner of a token, if it has been minted and not burned
+        pub(external) fn owner_of(&self, token_id: u64) -> Option<AccountId> {
+            self.owner_of_impl(token_id)
         }
 
-        /// Transfers a token_id from a specified address to another specified address
-        pub(external) fn transfer_from(&mut self, to: AccountId, token_id: u64) -> bool {
-            // make the transfer immediately if caller is the owner
-            if self.is_token_owner(&env.caller(), token_id) {
-                let result = self.transfer_impl(env.caller(), to, token_id);
-                if result == true {
-                    env.emit(EventTransfer { from: env.caller(), to: to, token_id: token_id });
+        /// Return the id of the latest snapshot, 0 if none was taken yet
+        pub(external) fn current_snapshot(&self) -> u64 {
+            let current_snapshot = *self.current_snapshot;
+            current_snapshot
+        }
+
+        /// Return the balance an owner had when a snapshot was taken
+        pub(external) fn balance_of_at(&self, owner: AccountId, snapshot_id: u64) -> TokenCount {
+            self.balance_at_impl(&owner, snapshot_id)
+        }
+
+        /// Return the circulating supply when a snapshot was taken
+        pub(external) fn total_supply_at(&self, snapshot_id: u64) -> TokenCount {
+            self.supply_at_impl(snapshot_id)
+        }
+
+        /// Freezes the current ownership state under a new snapshot id
+        pub(external) fn snapshot(&mut self) -> Result<u64, Error> {
+            diagnosed!(env, snapshot, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
                 }
-                return result;
 
-            // not owner: check if caller is approved to move the token
-            } else {
-                let approval = self.approvals.get(&token_id);
-                if let None = approval {
-                    return false;
+                let snapshot_id = *self.current_snapshot + 1;
+                self.current_snapshot.set(snapshot_id);
+
+                env.emit(EventSnapshot { caller: env.caller(), snapshot_id: snapshot_id });
+                Ok(snapshot_id)
+            })
+        }
+
+        /// Return the account allowed to bridge tokens, if any
+        pub(external) fn bridge(&self) -> Option<AccountId> {
+            let bridge = *self.bridge;
+            bridge
+        }
+
+        /// Return whether a token has been bridged out and not yet bridged back in
+        pub(external) fn is_bridged_out(&self, token_id: u64) -> bool {
+            self.bridged_out.get(&token_id).is_some()
+        }
+
+        /// Sets or clears the account allowed to bridge tokens
+        pub(external) fn set_bridge(&mut self, bridge: Option<AccountId>) -> Result<(), Error> {
+            diagnosed!(env, set_bridge, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                self.bridge.set(bridge);
+                env.emit(EventBridgeSet { caller: env.caller(), bridge: bridge });
+                Ok(())
+            })
+        }
+
+        /// Burns a token the bridge is authorized to move and records it as bridged out
+        pub(external) fn bridge_out(&mut self, token_id: u64, dest_chain: u32, dest_account: Vec<u8>) -> Result<(), Error> {
+            diagnosed!(env, bridge_out, {
+                if *self.bridge != Some(env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+
+                let owner = self.owner_of_impl(token_id).ok_or(Error::TokenNotFound)?;
+
+                // the owner must have approved the bridge to move the token
+                if !self.is_authorized(&env.caller(), &owner, token_id) {
+                    return Err(Error::NotApproved);
+                }
+
+                self.burn_impl(owner, token_id, env.block_number())?;
+                self.bridged_out.insert(token_id, true);
+
+                env.emit(EventBurn { owner: owner, token_id: token_id });
+                env.emit(EventBridgeOut { owner: owner, token_id: token_id, dest_chain: dest_chain, dest_account: dest_account });
+                Ok(())
+            })
+        }
+
+        /// Mints a previously bridged-out token back to an owner, on proof of its remote burn
+        pub(external) fn bridge_in(&mut self, token_id: u64, owner: AccountId, proof: Vec<u8>) -> Result<(), Error> {
+            diagnosed!(env, bridge_in, {
+                if *self.bridge != Some(env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+
+                // only tokens that left through the bridge can come back, and only once
+                if self.bridged_out.get(&token_id).is_none() {
+                    return Err(Error::NotFound);
+                }
+                self.restore_impl(owner, token_id, env.block_number())?;
+                self.bridged_out.remove(&token_id);
+
+                env.emit(EventConsecutiveTransfer { from_id: token_id, to_id: token_id, owner: owner });
+                env.emit(EventBridgeIn { owner: owner, token_id: token_id, proof: proof });
+                Ok(())
+            })
+        }
+
+        /// Return the destination (dest_chain, dest_account) of a token held in bridge custody
+        pub(external) fn bridge_lock_of(&self, token_id: u64) -> Option<(u32, Vec<u8>)> {
+            self.bridge_locks.get(&token_id).map(|(_, dest_chain, dest_account)| (*dest_chain, dest_account.clone()))
+        }
+
+        /// Takes a token the bridge is authorized to move into custody, recording its destination
+        pub(external) fn bridge_lock(&mut self, token_id: u64, dest_chain: u32, dest_account: Vec<u8>) -> Result<(), Error> {
+            diagnosed!(env, bridge_lock, {
+                if *self.bridge != Some(env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+
+                let owner = self.owner_of_impl(token_id).ok_or(Error::TokenNotFound)?;
+                if !self.is_authorized(&env.caller(), &owner, token_id) {
+                    return Err(Error::NotApproved);
+                }
+
+                // only a token free to move on its own can be taken into custody
+                self.before_token_transfer(Some(owner), None, token_id, 1, env.block_number(), None)?;
+
+                self.clear_approval(owner, token_id);
+                self.bridge_locks.insert(token_id, (owner, dest_chain, dest_account.clone()));
+
+                env.emit(EventBridgeLock { owner: owner, token_id: token_id, dest_chain: dest_chain, dest_account: dest_account });
+                Ok(())
+            })
+        }
+
+        /// Returns a token in custody to the owner it was locked from, e.g. after a failed remote transfer
+        pub(external) fn bridge_unlock(&mut self, token_id: u64) -> Result<(), Error> {
+            diagnosed!(env, bridge_unlock, {
+                if *self.bridge != Some(env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+
+                let (owner, _, _) = self.bridge_locks.remove(&token_id).ok_or(Error::NotFound)?;
+
+                env.emit(EventBridgeUnlock { owner: owner, token_id: token_id });
+                Ok(())
+            })
+        }
+
+        /// Releases a token in custody to a new owner, once it has come back from the remote chain
+        pub(external) fn bridge_release(&mut self, token_id: u64, to: AccountId) -> Result<(), Error> {
+            diagnosed!(env, bridge_release, {
+                if *self.bridge != Some(env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+                if is_zero_account(&to) {
+                    return Err(Error::ZeroAccount);
                 }
 
-                //carry out transfer if caller is approved
-                if *approval.unwrap() == env.caller() {
-                    // carry out the actual transfer
-                    let result = self.transfer_impl(env.caller(), to, token_id);
-                    if result == true {
-                        env.emit(EventTransfer { from: env.caller(), to: to, token_id: token_id });
+                let lock = self.bridge_locks.get(&token_id).cloned().ok_or(Error::NotFound)?;
+                let owner = lock.0;
+                let mut moved = Vec::new();
+                if owner != to {
+                    // the custody lock is lifted for the transfer hook to pass the token, and put
+                    // back if the hook or the move refuses it
+                    self.bridge_locks.remove(&token_id);
+                    let released = self.before_token_transfer(Some(owner), Some(to), token_id, 1, env.block_number(), None)
+                        .and_then(|_| self.move_token(owner, to, token_id, env.block_number()));
+                    match released {
+                        Ok(token_ids) => moved = token_ids,
+                        Err(error) => {
+                            self.bridge_locks.insert(token_id, lock);
+                            return Err(error);
+                        }
                     }
-                    return result;
-                } else {
-                    return false;
                 }
-            }
+                self.bridge_locks.remove(&token_id);
+
+                env.emit(EventBridgeUnlock { owner: to, token_id: token_id });
+                for moved_id in moved {
+                    env.emit(EventTransfer { from: owner, to: to, token_id: moved_id });
+                }
+                Ok(())
+            })
         }
 
-        /// Mints a specified amount of new tokens to a given address
-        pub(external) fn mint(&mut self, to: AccountId, value: u64) -> bool {
-            if env.caller() != *self.owner {
-                return false;
-            }
+        /// Return the claim hash a token is escrowed against as a gift, if any
+        pub(external) fn gift_of(&self, token_id: u64) -> Option<Hash> {
+            self.gift_hashes.get(&token_id).cloned()
+        }
 
-            // carry out the actual minting
-            if self.mint_impl(to, value) == true {
-                env.emit(EventMint { owner: to, value: value });
-                return true;
-            }
-            false
+        /// Escrows one of the caller's tokens as a gift for whoever presents the secret whose
+        /// BLAKE2b-256 hash is claim_hash; the token stays in escrow until claimed or cancelled
+        pub(external) fn create_gift(&mut self, token_id: u64, claim_hash: Hash) -> Result<(), Error> {
+            diagnosed!(env, create_gift, {
+                if !self.is_token_owner(&env.caller(), token_id) {
+                    return Err(Error::NotOwner);
+                }
+                if self.gifts.get(&claim_hash).is_some() {
+                    return Err(Error::Duplicate);
+                }
+
+                // only a token free to move on its own can be escrowed
+                self.before_token_transfer(Some(env.caller()), None, token_id, 1, env.block_number(), None)?;
+
+                self.clear_approval(env.caller(), token_id);
+                self.gifts.insert(claim_hash, (env.caller(), token_id));
+                self.gift_hashes.insert(token_id, claim_hash);
+
+                env.emit(EventGiftCreated { sender: env.caller(), token_id: token_id, claim_hash: claim_hash });
+                Ok(())
+            })
         }
 
-        /// Approves or disapproves an Account to send token on behalf of an owner
-        pub(external) fn approval(&mut self, to: AccountId, token_id: u64, approved: bool) -> bool {
-            // return if caller is not the token owner
-            let token_owner = self.id_to_owner.get(&token_id);
-            if let None = token_owner {
-                return false;
-            }
+        /// Commits the caller to claiming a gift, ahead of revealing its secret. The commitment
+        /// is the BLAKE2b-256 hash of the SCALE encoded (secret, claimer)
+        pub(external) fn commit_gift_claim(&mut self, commitment: Hash) -> Result<(), Error> {
+            diagnosed!(env, commit_gift_claim, {
+                if self.gift_commitments.get(&commitment).is_some() {
+                    return Err(Error::Duplicate);
+                }
+                self.gift_commitments.insert(commitment, env.block_number());
+                env.emit(EventGiftClaimCommitted { claimer: env.caller(), commitment: commitment });
+                Ok(())
+            })
+        }
 
-            let token_owner = *token_owner.unwrap();
-            if token_owner != env.caller() {
-                return false;
-            }
+        /// Hands an escrowed gift to the caller, who presents the secret it was created with.
+        /// The caller has to have committed to (secret, caller) in an earlier block, so a secret
+        /// seen in a pending claim cannot be replayed by anyone else
+        pub(external) fn claim_gift(&mut self, secret: Vec<u8>) -> Result<(), Error> {
+            diagnosed!(env, claim_gift, {
+                let claim_hash = Hash::from(blake2_256(&secret));
+                let (sender, token_id) = self.gifts.get(&claim_hash).cloned().ok_or(Error::NotFound)?;
+                if sender == env.caller() {
+                    return Err(Error::SelfTransfer);
+                }
+                let commitment = hash_of(&(secret, env.caller()));
+                let committed_at = self.gift_commitments.get(&commitment).cloned();
+                if let None = committed_at {
+                    return Err(Error::NotFound);
+                }
+                if committed_at.unwrap() >= env.block_number() {
+                    return Err(Error::NotReady);
+                }
 
-            let approvals = self.approvals.get(&token_id);
+                // the escrow is lifted for the transfer hook to pass the token, and put back if
+                // the hook or the move refuses it
+                self.gifts.remove(&claim_hash);
+                self.gift_hashes.remove(&token_id);
+                let claimed = self.before_token_transfer(Some(sender), Some(env.caller()), token_id, 1, env.block_number(), None)
+                    .and_then(|_| self.move_token(sender, env.caller(), token_id, env.block_number()));
+                let moved = match claimed {
+                    Ok(token_ids) => token_ids,
+                    Err(error) => {
+                        self.gifts.insert(claim_hash, (sender, token_id));
+                        self.gift_hashes.insert(token_id, claim_hash);
+                        return Err(error);
+                    }
+                };
+                self.gift_commitments.remove(&commitment);
 
-            // insert approval if
-            if let None = approvals {
-                if approved == true {
-                    self.approvals.insert(token_id, to);
+                env.emit(EventGiftClaimed { sender: sender, recipient: env.caller(), token_id: token_id });
+                for moved_id in moved {
+                    env.emit(EventTransfer { from: sender, to: env.caller(), token_id: moved_id });
+                }
+                Ok(())
+            })
+        }
+
+        /// Takes a gift back out of escrow; only its sender can cancel it
+        pub(external) fn cancel_gift(&mut self, token_id: u64) -> Result<(), Error> {
+            diagnosed!(env, cancel_gift, {
+                let claim_hash = self.gift_hashes.get(&token_id).cloned().ok_or(Error::NotFound)?;
+                let (sender, _) = self.gifts.get(&claim_hash).cloned().ok_or(Error::NotFound)?;
+                if sender != env.caller() {
+                    return Err(Error::Unauthorized);
+                }
+
+                self.gifts.remove(&claim_hash);
+                self.gift_hashes.remove(&token_id);
+
+                env.emit(EventGiftCancelled { sender: sender, token_id: token_id });
+                Ok(())
+            })
+        }
+
+        /// Return a proposal and its tally
+        pub(external) fn proposal_of(&self, proposal_id: u64) -> Option<Proposal> {
+            self.proposals.get(&proposal_id).cloned()
+        }
+
+        /// Sets how long proposals stay open and the quorum they need
+        pub(external) fn set_governance(&mut self, voting_period: BlockNumber, quorum_bps: u32) -> Result<(), Error> {
+            diagnosed!(env, set_governance, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                if !is_valid_quorum(quorum_bps) {
+                    return Err(Error::InvalidInput);
+                }
+
+                self.voting_period.set(voting_period);
+                self.quorum_bps.set(quorum_bps);
+                env.emit(EventGovernanceSet { caller: env.caller(), voting_period: voting_period, quorum_bps: quorum_bps });
+                Ok(())
+            })
+        }
+
+        /// Opens a proposal for one of the admin actions holders can vote on; votes are weighed by
+        /// voting power at a fresh snapshot. One proposal is open at a time, and none until the
+        /// owner has set a voting period and a quorum
+        pub(external) fn propose(&mut self, action: Action) -> Result<u64, Error> {
+            diagnosed!(env, propose, {
+                // only holders can put proposals forward
+                if *self.owner_to_token_count.get(&env.caller()).unwrap_or(&0) == 0 {
+                    return Err(Error::NoVotingPower);
+                }
+                // without a voting period and a quorum a lone holder could pass anything
+                if *self.voting_period == 0 || *self.quorum_bps == 0 {
+                    return Err(Error::NotReady);
+                }
+                if !action.is_proposable() {
+                    return Err(Error::Unauthorized);
+                }
+                // each proposal takes a snapshot, so a new one waits for the last one to close
+                let last_id = *self.total_proposals;
+                let open = self.proposals.get(&last_id).map(|p| env.block_number() <= p.end_block).unwrap_or(false);
+                if open == true {
+                    return Err(Error::LimitReached);
+                }
+                self.check_action(&action)?;
+
+                let snapshot_id = *self.current_snapshot + 1;
+                self.current_snapshot.set(snapshot_id);
+                env.emit(EventSnapshot { caller: env.caller(), snapshot_id: snapshot_id });
+
+                let proposal_id = *self.total_proposals + 1;
+                let end_block = env.block_number().checked_add(*self.voting_period).ok_or(Error::Overflow)?;
+                self.proposals.insert(proposal_id, Proposal {
+                    action: action,
+                    snapshot_id: snapshot_id,
+                    end_block: end_block,
+                    votes_for: 0,
+                    votes_against: 0,
+                    executed: false,
+                });
+                self.total_proposals.set(proposal_id);
+
+                env.emit(EventProposalCreated { proposer: env.caller(), proposal_id: proposal_id, snapshot_id: snapshot_id, end_block: end_block });
+                Ok(proposal_id)
+            })
+        }
+
+        /// Votes on an open proposal with the voting power the caller held at its snapshot
+        pub(external) fn vote(&mut self, proposal_id: u64, support: bool) -> Result<(), Error> {
+            diagnosed!(env, vote, {
+                let proposal = self.proposals.get(&proposal_id).cloned();
+                if let None = proposal {
+                    return Err(Error::NotFound);
+                }
+                let mut proposal = proposal.unwrap();
+
+                if env.block_number() > proposal.end_block {
+                    return Err(Error::Expired);
+                }
+                if self.proposal_votes.get(&(proposal_id, env.caller())).is_some() {
+                    return Err(Error::Duplicate);
+                }
+
+                let votes = self.votes_at_impl(&env.caller(), proposal.snapshot_id);
+                if votes == 0 {
+                    return Err(Error::NoVotingPower);
+                }
+
+                if support == true {
+                    proposal.votes_for = proposal.votes_for.checked_add(votes).ok_or(Error::Overflow)?;
                 } else {
-                    return false;
+                    proposal.votes_against = proposal.votes_against.checked_add(votes).ok_or(Error::Overflow)?;
                 }
+                self.proposals.insert(proposal_id, proposal);
+                self.proposal_votes.insert((proposal_id, env.caller()), support);
 
-            } else {
-                let existing = *approvals.unwrap();
+                env.emit(EventVoteCast { voter: env.caller(), proposal_id: proposal_id, support: support, votes: votes });
+                Ok(())
+            })
+        }
 
-                // remove existing owner if disapproving
-                if existing == to && approved == false {
-                    self.approvals.remove(&token_id);
+        /// Schedules a proposal's action behind the timelock once voting has closed with a
+        /// majority in favour and quorum reached
+        pub(external) fn execute(&mut self, proposal_id: u64) -> Result<(), Error> {
+            diagnosed!(env, execute, {
+                let proposal = self.proposals.get(&proposal_id).cloned();
+                if let None = proposal {
+                    return Err(Error::NotFound);
                 }
+                let mut proposal = proposal.unwrap();
 
-                // overwrite or insert if approving is true
-                if approved == true {
-                    self.approvals.insert(token_id, to);
+                if proposal.executed == true {
+                    return Err(Error::AlreadyExecuted);
+                }
+                if env.block_number() <= proposal.end_block {
+                    return Err(Error::NotReady);
+                }
+                if proposal.votes_for <= proposal.votes_against {
+                    return Err(Error::ProposalRejected);
                 }
-            }
 
-            env.emit(EventApproval { owner: env.caller(), spender: to, token_id: token_id, approved: approved });
-            true
+                // quorum is measured against the supply at the proposal's snapshot
+                let supply = self.supply_at_impl(proposal.snapshot_id);
+                if (proposal.votes_for as u128) * 10_000 < (supply as u128) * (*self.quorum_bps as u128) {
+                    return Err(Error::ProposalRejected);
+                }
+
+                let (schedule_id, eta) = self.schedule_impl(proposal.action.clone(), env.block_number(), false)?;
+
+                proposal.executed = true;
+                self.proposals.insert(proposal_id, proposal);
+
+                env.emit(EventProposalExecuted { caller: env.caller(), proposal_id: proposal_id });
+                env.emit(EventActionScheduled { caller: env.caller(), schedule_id: schedule_id, eta: eta });
+                Ok(())
+            })
         }
-    }
 
-    /// Private Methods
-    impl NFToken {
+        /// Return the accounts that can submit and confirm admin requests
+        pub(external) fn signers(&self) -> Vec<AccountId> {
+            (*self.signers).clone()
+        }
 
-        fn is_token_owner(&self, of: &AccountId, token_id: u64) -> bool {
-            let owner = self.id_to_owner.get(&token_id);
-            if let None = owner {
-                return false;
-            }
-            let owner = *owner.unwrap();
-            if owner != *of {
-                return false;
-            }
-            true
+        /// Return the confirmations an admin request needs
+        pub(external) fn signer_threshold(&self) -> u32 {
+            *self.signer_threshold
         }
 
-        /// Transfers token from a specified address to another address.
-        fn transfer_impl(&mut self, from: AccountId, to: AccountId, token_id: u64) -> bool {
-            if !self.is_token_owner(&from, token_id) {
-                return false;
-            }
+        /// Return an admin request and whether it has been carried out
+        pub(external) fn admin_request_of(&self, request_id: u64) -> Option<AdminRequest> {
+            self.admin_requests.get(&request_id).cloned()
+        }
 
-            self.id_to_owner.insert(token_id, to);
+        /// Return the confirmations an admin request has from the current signers
+        pub(external) fn confirmations_of(&self, request_id: u64) -> u32 {
+            self.confirmations_impl(request_id)
+        }
 
-            //update owner token counts
-            let from_owner_count = *self.owner_to_token_count.get(&from).unwrap_or(&0);
-            let to_owner_count = *self.owner_to_token_count.get(&to).unwrap_or(&0);
+        /// Sets the accounts that confirm admin requests and how many of them have to agree
+        pub(external) fn set_signers(&mut self, signers: Vec<AccountId>, threshold: u32) -> Result<(), Error> {
+            diagnosed!(env, set_signers, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                if !is_valid_signers(&signers, threshold) {
+                    return Err(Error::InvalidInput);
+                }
 
-            self.owner_to_token_count.insert(from, from_owner_count - 1);
-            self.owner_to_token_count.insert(to, to_owner_count + 1);
-            true
+                self.signers.set(signers.clone());
+                self.signer_threshold.set(threshold);
+                env.emit(EventSignersSet { caller: env.caller(), signers: signers, threshold: threshold });
+                Ok(())
+            })
         }
 
-        /// minting of new tokens implementation
-        fn mint_impl(&mut self, receiver: AccountId, value: u64) -> bool {
+        /// Submits an admin action for the signers to confirm, counting the submitter's confirmation
+        pub(external) fn submit_admin_request(&mut self, action: Action) -> Result<u64, Error> {
+            diagnosed!(env, submit_admin_request, {
+                if !self.signers.contains(&env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+                self.check_action(&action)?;
+
+                let request_id = *self.total_admin_requests + 1;
+                self.admin_requests.insert(request_id, AdminRequest { action: action, executed: false });
+                self.total_admin_requests.set(request_id);
+                env.emit(EventAdminRequestSubmitted { signer: env.caller(), request_id: request_id });
 
-            let start_id = *self.total_minted + 1;
-            let stop_id = *self.total_minted + value;
+                self.admin_confirmations.insert((request_id, env.caller()), true);
+                env.emit(EventAdminRequestConfirmed { signer: env.caller(), request_id: request_id });
 
-            // loop through new tokens being minted
-            for token_id in start_id..stop_id {
-                self.id_to_owner.insert(token_id, receiver);
-            }
+                if let Some((schedule_id, eta)) = self.schedule_admin_request(request_id, env.block_number())? {
+                    env.emit(EventAdminRequestExecuted { request_id: request_id });
+                    env.emit(EventActionScheduled { caller: env.caller(), schedule_id: schedule_id, eta: eta });
+                }
+                Ok(request_id)
+            })
+        }
 
-            // update total supply of owner
-            let from_owner_count = *self.owner_to_token_count.get(&self.owner).unwrap_or(&0);
-            self.owner_to_token_count.insert(*self.owner, from_owner_count + value);
+        /// Confirms an admin request, scheduling its action once the threshold is reached
+        pub(external) fn confirm_admin_request(&mut self, request_id: u64) -> Result<(), Error> {
+            diagnosed!(env, confirm_admin_request, {
+                if !self.signers.contains(&env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
 
-            // update total supply
-            self.total_minted += value;
-            true
+                let request = self.admin_requests.get(&request_id).cloned();
+                if let None = request {
+                    return Err(Error::NotFound);
+                }
+                let request = request.unwrap();
+                if request.executed == true {
+                    return Err(Error::AlreadyExecuted);
+                }
+                if self.admin_confirmations.get(&(request_id, env.caller())).is_some() {
+                    return Err(Error::Duplicate);
+                }
+                // an action that could no longer be carried out is not worth confirming
+                self.check_action(&request.action)?;
+
+                self.admin_confirmations.insert((request_id, env.caller()), true);
+                env.emit(EventAdminRequestConfirmed { signer: env.caller(), request_id: request_id });
+
+                if let Some((schedule_id, eta)) = self.schedule_admin_request(request_id, env.block_number())? {
+                    env.emit(EventAdminRequestExecuted { request_id: request_id });
+                    env.emit(EventActionScheduled { caller: env.caller(), schedule_id: schedule_id, eta: eta });
+                }
+                Ok(())
+            })
         }
-    }
-}
 
-#[cfg(all(test, feature = "test-env"))]
-mod tests {
-    use super::*;
-    use std::convert::TryFrom;
+        /// Return the blocks a scheduled admin action waits before it can be carried out
+        pub(external) fn timelock_delay(&self) -> BlockNumber {
+            *self.timelock_delay
+        }
 
-    #[test]
-    fn it_works() {
+        /// Return a pending scheduled action and the block it can be carried out from
+        pub(external) fn scheduled_of(&self, schedule_id: u64) -> Option<ScheduledAction> {
+            self.scheduled.get(&schedule_id).cloned()
+        }
 
-        // deploying and minting initial tokens
-        let mut _nftoken = NFToken::deploy_mock(100);
-        let alice = AccountId::try_from([0x0; 32]).unwrap();
-        let bob = AccountId::try_from([0x1; 32]).unwrap();
-        let charlie = AccountId::try_from([0x2; 32]).unwrap();
-        let dave = AccountId::try_from([0x3; 32]).unwrap();
+        /// Schedules an admin action to be carried out once the timelock delay has passed
+        pub(external) fn schedule(&mut self, action: Action) -> Result<u64, Error> {
+            diagnosed!(env, schedule, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
 
-        let total_minted = _nftoken.total_minted();
-        assert_eq!(total_minted, 100);
+                let (schedule_id, eta) = self.schedule_impl(action, env.block_number(), true)?;
+                env.emit(EventActionScheduled { caller: env.caller(), schedule_id: schedule_id, eta: eta });
+                Ok(schedule_id)
+            })
+        }
 
-        // transferring token_id from alice to bob
-        _nftoken.transfer(bob, 1);
+        /// Carries out a scheduled action whose delay has passed; anyone can trigger it
+        pub(external) fn execute_scheduled(&mut self, schedule_id: u64) -> Result<(), Error> {
+            diagnosed!(env, execute_scheduled, {
+                let scheduled = self.scheduled.get(&schedule_id).cloned();
+                if let None = scheduled {
+                    return Err(Error::NotFound);
+                }
+                let scheduled = scheduled.unwrap();
+                if env.block_number() < scheduled.eta {
+                    return Err(Error::NotReady);
+                }
 
-        let alice_balance = _nftoken.balance_of(alice);
-        let mut bob_balance = _nftoken.balance_of(bob);
+                let previous_owner = *self.owner;
+                self.apply_action(scheduled.action.clone())?;
+                self.scheduled.remove(&schedule_id);
 
-        assert_eq!(alice_balance, 99);
-        assert_eq!(bob_balance, 1);
+                env.emit(EventScheduledExecuted { caller: env.caller(), schedule_id: schedule_id });
 
-        // approve charlie to send token_id 2 from alice's account
-        _nftoken.approval(charlie, 2, true);
-        assert_eq!(_nftoken.is_approved(2, charlie), true);
+                // report the change just as its setter would, so every admin change shows in the events
+                let caller = env.caller();
+                match scheduled.action {
+                    Action::SetRenewalPeriod(period) => env.emit(EventRenewalPeriodSet { caller: caller, period: period }),
+                    Action::SetFusionCooldown(cooldown) => env.emit(EventFusionCooldownSet { caller: caller, cooldown: cooldown }),
+                    Action::TransferOwnership(new_owner) => env.emit(EventOwnershipTransferred { previous_owner: previous_owner, new_owner: new_owner }),
+                    Action::SetTimelockDelay(delay) => env.emit(EventTimelockDelaySet { caller: caller, delay: delay }),
+                    Action::SetMinter(minter) => env.emit(EventMinterSet { caller: caller, minter: minter }),
+                    Action::SetBridge(bridge) => env.emit(EventBridgeSet { caller: caller, bridge: bridge }),
+                    Action::SetHistoryLength(length) => env.emit(EventHistoryLengthSet { caller: caller, length: length }),
+                    Action::SetPaused(paused) => env.emit(EventPaused { caller: caller, paused: paused }),
+                    Action::SetReserve(max_supply, reserved) => env.emit(EventReserveSet { caller: caller, max_supply: max_supply, reserved: reserved }),
+                    Action::SetMaxPerHolder(max_per_holder) => env.emit(EventMaxPerHolderSet { caller: caller, max_per_holder: max_per_holder }),
+                    Action::SetTransferCooldown(cooldown) => env.emit(EventTransferCooldownSet { caller: caller, cooldown: cooldown }),
+                    Action::SetOperatorFilter(enabled) => env.emit(EventOperatorFilterSet { caller: caller, enabled: enabled }),
+                    Action::RegisterOperator(operator, registered) => env.emit(EventOperatorRegistered { caller: caller, operator: operator, registered: registered }),
+                    Action::SetOracle(oracle, interval) => env.emit(EventOracleSet { caller: caller, oracle: oracle, interval: interval }),
+                    Action::SetAllowedUriSchemes(schemes) => env.emit(EventUriSchemesSet { caller: caller, schemes: schemes }),
+                    Action::SetGovernance(voting_period, quorum_bps) => env.emit(EventGovernanceSet { caller: caller, voting_period: voting_period, quorum_bps: quorum_bps }),
+                    Action::SetSigners(signers, threshold) => env.emit(EventSignersSet { caller: caller, signers: signers, threshold: threshold }),
+                    Action::SetSuperAdmin(super_admin) => env.emit(EventSuperAdminSet { caller: caller, super_admin: super_admin }),
+                }
+                Ok(())
+            })
+        }
 
-        // overwrite charlie's approval with dave's approval
-        _nftoken.approval(dave, 2, true);
-        assert_eq!(_nftoken.is_approved(2, dave), true);
+        /// Cancels a pending action the owner scheduled; actions passed by holders or signers
+        /// cannot be cancelled by the owner they may be meant to check
+        pub(external) fn cancel_scheduled(&mut self, schedule_id: u64) -> Result<(), Error> {
+            diagnosed!(env, cancel_scheduled, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                let scheduled = self.scheduled.get(&schedule_id);
+                if let None = scheduled {
+                    return Err(Error::NotFound);
+                }
+                if scheduled.unwrap().cancellable == false {
+                    return Err(Error::Unauthorized);
+                }
+                self.scheduled.remove(&schedule_id);
 
-        // remove dave from approvals
-        _nftoken.approval(dave, 2, false);
-        assert_eq!(_nftoken.is_approved(2, dave), false);
+                env.emit(EventScheduledCancelled { caller: env.caller(), schedule_id: schedule_id });
+                Ok(())
+            })
+        }
 
-        // transfer_from function: caller is token owner
-        _nftoken.approval(charlie, 3, true);
-        assert_eq!(_nftoken.is_approved(3, charlie), true);
+        /// Return the most recent (from, to, block) transfers of a token, oldest first
+        pub(external) fn history_of(&self, token_id: u64) -> Vec<(AccountId, AccountId, BlockNumber)> {
+            let history = self.transfer_history.get(&token_id).cloned().unwrap_or_default();
+            // a shortened history length applies before the token next moves
+            let skip = history.len().saturating_sub(*self.history_length as usize);
+            history.into_iter().skip(skip).collect()
+        }
 
-        _nftoken.transfer_from(bob, 3);
-        bob_balance = _nftoken.balance_of(bob);
+        /// Return the committed rarity score of a token
+        pub(external) fn rarity_of(&self, token_id: u64) -> Option<u32> {
+            self.rarity_scores.get(&token_id).cloned()
+        }
 
-        assert_eq!(bob_balance, 2);
+        /// Commits rarity scores for minted tokens; a committed score can never change,
+        /// so the whole batch is refused if any token already has one
+        pub(external) fn commit_rarity(&mut self, token_ids: Vec<u64>, scores: Vec<u32>) -> Result<(), Error> {
+            diagnosed!(env, commit_rarity, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // once a root is committed, scores only come from it
+                if self.rarity_root.is_some() {
+                    return Err(Error::Duplicate);
+                }
+                if token_ids.len() != scores.len() {
+                    return Err(Error::InvalidInput);
+                }
+
+                for (i, token_id) in token_ids.iter().enumerate() {
+                    if *token_id == 0 || *token_id > *self.total_minted {
+                        return Err(Error::TokenNotFound);
+                    }
+                    if self.rarity_scores.get(token_id).is_some() || token_ids[..i].contains(token_id) {
+                        return Err(Error::Duplicate);
+                    }
+                }
+
+                for (token_id, score) in token_ids.iter().zip(scores.iter()) {
+                    self.rarity_scores.insert(*token_id, *score);
+                }
+
+                env.emit(EventRarityCommitted { caller: env.caller(), token_ids: token_ids });
+                Ok(())
+            })
+        }
+
+        /// Return the Merkle root rarity scores are revealed against, if committed
+        pub(external) fn rarity_root(&self) -> Option<Hash> {
+            let rarity_root = *self.rarity_root;
+            rarity_root
+        }
+
+        /// Commits the Merkle root of all rarity scores at once, leaving each score to be revealed
+        /// with its proof; like the scores themselves, the root can never change
+        pub(external) fn commit_rarity_root(&mut self, root: Hash) -> Result<(), Error> {
+            diagnosed!(env, commit_rarity_root, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                if self.rarity_root.is_some() {
+                    return Err(Error::Duplicate);
+                }
+
+                self.rarity_root.set(Some(root));
+                env.emit(EventRarityRootCommitted { caller: env.caller(), root: root });
+                Ok(())
+            })
+        }
+
+        /// Records a token's score from the committed rarity root, proven by the hash of
+        /// (token_id, score) and its Merkle proof; anyone can reveal a score
+        pub(external) fn reveal_rarity(&mut self, token_id: u64, score: u32, proof: Vec<Hash>) -> Result<(), Error> {
+            diagnosed!(env, reveal_rarity, {
+                let rarity_root = *self.rarity_root;
+                if let None = rarity_root {
+                    return Err(Error::NotFound);
+                }
+                if token_id == 0 || token_id > *self.total_minted {
+                    return Err(Error::TokenNotFound);
+                }
+                if self.rarity_scores.get(&token_id).is_some() {
+                    return Err(Error::Duplicate);
+                }
+                if !verify_merkle_proof(hash_of(&(token_id, score)), &proof, rarity_root.unwrap()) {
+                    return Err(Error::InvalidInput);
+                }
+
+                self.rarity_scores.insert(token_id, score);
+                let mut token_ids = Vec::new();
+                token_ids.push(token_id);
+                env.emit(EventRarityCommitted { caller: env.caller(), token_ids: token_ids });
+                Ok(())
+            })
+        }
+
+        /// Return the guardians a holder has registered
+        pub(external) fn guardians_of(&self, owner: AccountId) -> Option<GuardianSet> {
+            self.guardians.get(&owner).cloned()
+        }
+
+        /// Return the recovery of a holder's tokens in progress
+        pub(external) fn recovery_of(&self, owner: AccountId) -> Option<RecoveryRequest> {
+            self.recoveries.get(&owner).cloned()
+        }
+
+        /// Registers the caller's guardians, how many of them have to agree to a recovery
+        /// and the blocks the caller then has to veto it; cancels a recovery in progress
+        pub(external) fn set_guardians(&mut self, guardians: Vec<AccountId>, threshold: u32, delay: BlockNumber) -> Result<(), Error> {
+            diagnosed!(env, set_guardians, {
+                if threshold == 0 || threshold as usize > guardians.len() {
+                    return Err(Error::InvalidInput);
+                }
+                for (i, guardian) in guardians.iter().enumerate() {
+                    if is_zero_account(guardian) || *guardian == env.caller() || guardians[..i].contains(guardian) {
+                        return Err(Error::InvalidInput);
+                    }
+                }
+
+                self.guardians.insert(env.caller(), GuardianSet { guardians: guardians, threshold: threshold, delay: delay });
+                self.recoveries.remove(&env.caller());
+                env.emit(EventGuardiansSet { owner: env.caller(), threshold: threshold, delay: delay });
+                Ok(())
+            })
+        }
+
+        /// Supports moving a holder's tokens to a recovery address, in place of any address the
+        /// caller backed before; the challenge delay starts, or starts over, once enough guardians
+        /// agree on an address, so no single guardian can hold the recovery to theirs
+        pub(external) fn support_recovery(&mut self, owner: AccountId, to: AccountId) -> Result<(), Error> {
+            diagnosed!(env, support_recovery, {
+                let guardian_set = self.guardians.get(&owner).cloned();
+                if let None = guardian_set {
+                    return Err(Error::NotFound);
+                }
+                let guardian_set = guardian_set.unwrap();
+                if !guardian_set.guardians.contains(&env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+                if is_zero_account(&to) || to == owner {
+                    return Err(Error::InvalidInput);
+                }
+
+                let mut recovery = self.recoveries.get(&owner).cloned()
+                    .unwrap_or(RecoveryRequest { supporters: Vec::new(), to: None, ready_at: None });
+                if recovery.supporters.contains(&(env.caller(), to)) {
+                    return Err(Error::Duplicate);
+                }
+
+                recovery.supporters.retain(|(guardian, _)| *guardian != env.caller());
+                recovery.supporters.push((env.caller(), to));
+                env.emit(EventRecoverySupported { guardian: env.caller(), owner: owner, to: to });
+
+                // guardians have to agree on where the tokens go; a recovery that lost its
+                // support stops, and one a threshold of guardians agree on takes its place
+                if let Some(current) = recovery.to {
+                    if recovery.support_for(&current) < guardian_set.threshold {
+                        recovery.to = None;
+                        recovery.ready_at = None;
+                    }
+                }
+                if recovery.to != Some(to) && recovery.support_for(&to) >= guardian_set.threshold {
+                    let ready_at = env.block_number().saturating_add(guardian_set.delay);
+                    recovery.to = Some(to);
+                    recovery.ready_at = Some(ready_at);
+                    env.emit(EventRecoveryStarted { owner: owner, to: to, ready_at: ready_at });
+                }
+                self.recoveries.insert(owner, recovery);
+                Ok(())
+            })
+        }
+
+        /// Cancels a recovery of the caller's tokens
+        pub(external) fn veto_recovery(&mut self) -> Result<(), Error> {
+            diagnosed!(env, veto_recovery, {
+                if self.recoveries.remove(&env.caller()).is_none() {
+                    return Err(Error::NotFound);
+                }
+                env.emit(EventRecoveryVetoed { owner: env.caller() });
+                Ok(())
+            })
+        }
+
+        /// Moves a holder's tokens to the recovery address once the challenge delay has passed
+        pub(external) fn complete_recovery(&mut self, owner: AccountId) -> Result<(), Error> {
+            diagnosed!(env, complete_recovery, {
+                let recovery = self.recoveries.get(&owner).cloned();
+                if let None = recovery {
+                    return Err(Error::NotFound);
+                }
+                let recovery = recovery.unwrap();
+                let to = match (recovery.to, recovery.ready_at) {
+                    (Some(to), Some(ready_at)) if env.block_number() >= ready_at => to,
+                    _ => return Err(Error::NotReady),
+                };
+
+                let swept = self.sweep_tokens(owner, to, env.block_number())?;
+                self.recoveries.remove(&owner);
+
+                for token_id in swept.iter() {
+                    env.emit(EventTransfer { from: owner, to: to, token_id: *token_id });
+                }
+                env.emit(EventRecovered { owner: owner, to: to, tokens: swept.len() as u32 });
+                Ok(())
+            })
+        }
+
+        /// Return a holder's backup address and the delay before it can sweep their tokens
+        pub(external) fn backup_of(&self, owner: AccountId) -> Option<(AccountId, BlockNumber)> {
+            self.backups.get(&owner).cloned()
+        }
+
+        /// Return the block from which a holder's backup can sweep their tokens, if it has announced it
+        pub(external) fn backup_ready_at(&self, owner: AccountId) -> Option<BlockNumber> {
+            self.backup_ready_at.get(&owner).cloned()
+        }
+
+        /// Registers, or removes with None, a backup address able to sweep the caller's tokens
+        /// after announcing it and waiting the delay; cancels an announcement in progress
+        pub(external) fn set_backup(&mut self, backup: Option<AccountId>, delay: BlockNumber) -> Result<(), Error> {
+            diagnosed!(env, set_backup, {
+                match backup {
+                    Some(account) => {
+                        if is_zero_account(&account) || account == env.caller() {
+                            return Err(Error::InvalidInput);
+                        }
+                        self.backups.insert(env.caller(), (account, delay));
+                    }
+                    None => {
+                        self.backups.remove(&env.caller());
+                    }
+                }
+                self.backup_ready_at.remove(&env.caller());
+
+                env.emit(EventBackupSet { owner: env.caller(), backup: backup, delay: delay });
+                Ok(())
+            })
+        }
+
+        /// Announces that the caller, as a holder's backup, will sweep their tokens once the delay passes
+        pub(external) fn announce_recovery(&mut self, owner: AccountId) -> Result<(), Error> {
+            diagnosed!(env, announce_recovery, {
+                let backup = self.backups.get(&owner).cloned();
+                if let None = backup {
+                    return Err(Error::NotFound);
+                }
+                let (backup, delay) = backup.unwrap();
+                if backup != env.caller() {
+                    return Err(Error::Unauthorized);
+                }
+                if self.backup_ready_at.get(&owner).is_some() {
+                    return Err(Error::Duplicate);
+                }
+
+                let ready_at = env.block_number().saturating_add(delay);
+                self.backup_ready_at.insert(owner, ready_at);
+                env.emit(EventBackupAnnounced { owner: owner, backup: backup, ready_at: ready_at });
+                Ok(())
+            })
+        }
+
+        /// Cancels an announced sweep of the caller's tokens
+        pub(external) fn cancel_backup_recovery(&mut self) -> Result<(), Error> {
+            diagnosed!(env, cancel_backup_recovery, {
+                if self.backup_ready_at.remove(&env.caller()).is_none() {
+                    return Err(Error::NotFound);
+                }
+                env.emit(EventBackupCancelled { owner: env.caller() });
+                Ok(())
+            })
+        }
+
+        /// Sweeps all of a holder's tokens to the calling backup once its announced delay has passed
+        pub(external) fn recover_all(&mut self, owner: AccountId) -> Result<(), Error> {
+            diagnosed!(env, recover_all, {
+                let backup = self.backups.get(&owner).cloned();
+                if let None = backup {
+                    return Err(Error::NotFound);
+                }
+                let (backup, _) = backup.unwrap();
+                if backup != env.caller() {
+                    return Err(Error::Unauthorized);
+                }
+                match self.backup_ready_at.get(&owner) {
+                    Some(ready_at) if env.block_number() >= *ready_at => (),
+                    _ => return Err(Error::NotReady),
+                }
+
+                let swept = self.sweep_tokens(owner, backup, env.block_number())?;
+                self.backup_ready_at.remove(&owner);
+
+                for token_id in swept.iter() {
+                    env.emit(EventTransfer { from: owner, to: backup, token_id: *token_id });
+                }
+                env.emit(EventRecovered { owner: owner, to: backup, tokens: swept.len() as u32 });
+                Ok(())
+            })
+        }
+
+        /// Return the nonce the next signed authorization of an account has to carry
+        pub(external) fn nonce_of(&self, account: AccountId) -> u64 {
+            let nonce = *self.nonces.get(&account).unwrap_or(&0);
+            nonce
+        }
+
+        /// Return the hash of the signing domain, (name, version, contract address), for wallets
+        /// to bind signatures to this deployment
+        pub(external) fn domain_separator(&self) -> Hash {
+            let domain_separator = *self.domain_separator;
+            domain_separator
+        }
+
+        /// Return whether a signature by an account over a message is valid under one of the
+        /// schemes this deployment accepts
+        pub(external) fn verify_signature(&self, signer: AccountId, message: Vec<u8>, signature: Vec<u8>) -> bool {
+            signatures::verify(&signer, &message, &signature)
+        }
+
+        /// Submits a call signed by another account, carried out with the signer as the caller.
+        /// The signature covers (domain separator, signer, call, nonce, deadline); the signer's
+        /// nonce moves on once the call succeeds, so it cannot be replayed
+        pub(external) fn relay(&mut self, signer: AccountId, call: RelayedCall, nonce: u64, deadline: BlockNumber, signature: Vec<u8>) -> Result<(), Error> {
+            diagnosed!(env, relay, {
+                if deadline < env.block_number() {
+                    return Err(Error::Expired);
+                }
+                if nonce != *self.nonces.get(&signer).unwrap_or(&0) {
+                    return Err(Error::InvalidInput);
+                }
+                let payload = (*self.domain_separator, signer, call.clone(), nonce, deadline).encode();
+                if signatures::verify(&signer, &payload, &signature) == false {
+                    return Err(Error::Unauthorized);
+                }
+
+                match call {
+                    RelayedCall::Transfer(to, token_id) => {
+                        let moved = self.transfer_impl(signer, to, token_id, env.block_number())?;
+                        for moved_id in moved {
+                            env.emit(EventTransfer { from: signer, to: to, token_id: moved_id });
+                        }
+                    }
+                    RelayedCall::Approval(to, token_id, approved) => {
+                        self.approval_impl(signer, to, token_id, approved)?;
+                        env.emit(EventApproval { owner: signer, spender: to, token_id: token_id, approved: approved });
+                    }
+                    RelayedCall::SetApprovalForAll(operator, approved) => {
+                        self.set_approval_for_all_impl(signer, operator, approved)?;
+                        env.emit(EventApprovalForAll { owner: signer, operator: operator, approved: approved });
+                    }
+                }
+                self.nonces.insert(signer, nonce.saturating_add(1));
+                env.emit(EventRelayed { relayer: env.caller(), signer: signer, nonce: nonce });
+                Ok(())
+            })
+        }
+
+        /// Moves the caller's nonce on, cancelling any authorization signed but not yet submitted
+        pub(external) fn invalidate_nonce(&mut self) -> Result<(), Error> {
+            diagnosed!(env, invalidate_nonce, {
+                let nonce = self.nonces.get(&env.caller()).unwrap_or(&0).saturating_add(1);
+                self.nonces.insert(env.caller(), nonce);
+                env.emit(EventNonceInvalidated { account: env.caller(), nonce: nonce });
+                Ok(())
+            })
+        }
+
+        /// Return the number of accounts holding at least one token
+        pub(external) fn holder_count(&self) -> u64 {
+            *self.holder_count
+        }
+
+        /// Return how many times a token has changed hands since it was minted
+        pub(external) fn transfer_count(&self, token_id: u64) -> u32 {
+            let transfers = *self.transfer_counts.get(&token_id).unwrap_or(&0);
+            transfers
+        }
+
+        /// Return the most recent transfers kept per token
+        pub(external) fn history_length(&self) -> u32 {
+            *self.history_length
+        }
+
+        /// Sets how many of the most recent transfers are kept per token; 0 stops recording
+        pub(external) fn set_history_length(&mut self, length: u32) -> Result<(), Error> {
+            diagnosed!(env, set_history_length, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                self.history_length.set(length);
+                env.emit(EventHistoryLengthSet { caller: env.caller(), length: length });
+                Ok(())
+            })
+        }
+
+        /// Return the account that votes with a holder's tokens
+        pub(external) fn delegate_of(&self, holder: AccountId) -> AccountId {
+            self.delegate_of_impl(&holder)
+        }
+
+        /// Return the votes an account holds through its own and delegated tokens
+        pub(external) fn voting_power_of(&self, account: AccountId) -> TokenCount {
+            let votes = *self.voting_power.get(&account).unwrap_or(&0);
+            votes
+        }
+
+        /// Return the votes an account held when a snapshot was taken
+        pub(external) fn voting_power_at(&self, account: AccountId, snapshot_id: u64) -> TokenCount {
+            self.votes_at_impl(&account, snapshot_id)
+        }
+
+        /// Delegates the votes of all of the caller's tokens, current and future, to an account
+        pub(external) fn delegate(&mut self, to: AccountId) -> Result<(), Error> {
+            diagnosed!(env, delegate, {
+                if is_zero_account(&to) {
+                    return Err(Error::ZeroAccount);
+                }
+
+                let from_delegate = self.delegate_of_impl(&env.caller());
+                if from_delegate == to {
+                    return Err(Error::Duplicate);
+                }
+
+                let balance = *self.owner_to_token_count.get(&env.caller()).unwrap_or(&0);
+                self.move_votes(from_delegate, to, balance)?;
+                if to == env.caller() {
+                    self.delegates.remove(&env.caller());
+                } else {
+                    self.delegates.insert(env.caller(), to);
+                }
+
+                env.emit(EventDelegateChanged { delegator: env.caller(), from_delegate: from_delegate, to_delegate: to });
+                Ok(())
+            })
+        }
+
+        /// Return the token at a position of an owner's holdings, for index < balance_of(owner)
+        pub(external) fn token_of_owner_by_index(&self, owner: AccountId, index: TokenCount) -> Option<u64> {
+            self.token_at_index(&owner, index)
+        }
+
+        /// Return the balance of the given address.
+        pub(external) fn balance_of(&self, owner: AccountId) -> TokenCount {
+            let balance = *self.owner_to_token_count.get(&owner).unwrap_or(&0);
+            balance
+        }
+
+        /// Return the balances of several accounts at once, in the order given
+        pub(external) fn balance_of_batch(&self, owners: Vec<AccountId>) -> Vec<TokenCount> {
+            owners
+                .iter()
+                .map(|owner| *self.owner_to_token_count.get(owner).unwrap_or(&0))
+                .collect()
+        }
+
+        /// Transfers a token_id to a specified address from the caller
+        pub(external) fn transfer(&mut self, to: AccountId, token_id: u64) -> Result<(), Error> {
+            diagnosed!(env, transfer, {
+                // carry out the actual transfer
+                let moved = self.transfer_impl(env.caller(), to, token_id, env.block_number())?;
+                for moved_id in moved {
+                    env.emit(EventTransfer { from: env.caller(), to: to, token_id: moved_id });
+                }
+                Ok(())
+            })
+        }
+
+        /// Transfers a token_id from a specified address to another specified address
+        pub(external) fn transfer_from(&mut self, to: AccountId, token_id: u64) -> Result<(), Error> {
+            diagnosed!(env, transfer_from, {
+                let owner = self.owner_of_impl(token_id);
+                if let None = owner {
+                    return Err(Error::TokenNotFound);
+                }
+                let owner = owner.unwrap();
+
+                // caller must be the owner, approved for the token, an operator of the owner,
+                // or an operator with transfers left in its allowance
+                let uses_allowance = !self.is_authorized(&env.caller(), &owner, token_id);
+                if uses_allowance && self.remaining_allowance_impl(&owner, &env.caller()) == 0 {
+                    return Err(Error::NotApproved);
+                }
+
+                // carry out the actual transfer
+                let moved = self.transfer_impl(owner, to, token_id, env.block_number())?;
+                if uses_allowance {
+                    let remaining = self.remaining_allowance_impl(&owner, &env.caller()) - 1;
+                    self.operator_allowances.insert((owner, env.caller()), remaining);
+                    env.emit(EventOperatorAllowance { owner: owner, operator: env.caller(), transfers: remaining });
+                }
+                for moved_id in moved {
+                    env.emit(EventTransfer { from: owner, to: to, token_id: moved_id });
+                }
+                Ok(())
+            })
+        }
+
+        /// Mints a specified amount of new tokens to a given address,
+        /// returning the first and last token_id minted
+        pub(external) fn mint(&mut self, to: AccountId, value: TokenCount) -> Result<(u64, u64), Error> {
+            diagnosed!(env, mint, {
+                if !self.is_minter(&env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+                if !self.fits_public_supply(value) {
+                    return Err(Error::SupplyCapExceeded);
+                }
+
+                // carry out the actual minting
+                let (first_id, last_id) = self.mint_impl(env.caller(), to, value, env.block_number())?;
+                env.emit(EventMint { owner: to, value: value });
+                env.emit(EventConsecutiveTransfer { from_id: first_id, to_id: last_id, owner: to });
+                Ok((first_id, last_id))
+            })
+        }
+
+        /// Mints one token to each recipient, starting at the cursor and handling at most
+        /// MAX_AIRDROP_BATCH recipients; returns the cursor to resume from, which equals
+        /// recipients.len() once the whole list is done
+        pub(external) fn airdrop(&mut self, recipients: Vec<AccountId>, cursor: u32) -> Result<u32, Error> {
+            diagnosed!(env, airdrop, {
+                if !self.is_minter(&env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+
+                let end = core::cmp::min(recipients.len() as u32, cursor.saturating_add(MAX_AIRDROP_BATCH));
+
+                // the whole chunk passes the transfer hook before anything is minted, so a refused
+                // call leaves no partial airdrop behind and can be retried from the same cursor
+                let mut incoming: Vec<(AccountId, TokenCount)> = Vec::new();
+                for to in recipients[cursor.min(end) as usize..end as usize].iter() {
+                    if is_zero_account(to) {
+                        continue;
+                    }
+                    match incoming.iter_mut().find(|(account, _)| account == to) {
+                        Some((_, count)) => *count += 1,
+                        None => incoming.push((*to, 1)),
+                    }
+                }
+                for (to, count) in incoming.iter() {
+                    self.before_token_transfer(None, Some(*to), *self.total_minted + 1, *count, env.block_number(), None)?;
+                }
+
+                for index in cursor..end {
+                    let to = recipients[index as usize];
+                    if !self.fits_public_supply(1) {
+                        return Ok(index);
+                    }
+                    match self.mint_impl(env.caller(), to, 1, env.block_number()) {
+                        Ok((token_id, _)) => {
+                            env.emit(EventMint { owner: to, value: 1 });
+                            env.emit(EventConsecutiveTransfer { from_id: token_id, to_id: token_id, owner: to });
+                        }
+                        // the zero account cannot receive and is skipped
+                        Err(Error::ZeroAccount) => (),
+                        Err(error) => return Err(error),
+                    }
+                }
+                Ok(core::cmp::max(end, cursor))
+            })
+        }
+
+        /// Return the Merkle root recipients currently claim against, if any
+        pub(external) fn claim_root(&self) -> Option<Hash> {
+            let claim_root = *self.claim_root;
+            claim_root.map(|(root, _)| root)
+        }
+
+        /// Return whether a leaf of the current claim root has been claimed
+        pub(external) fn is_claimed(&self, index: u64) -> bool {
+            self.is_claimed_impl(index)
+        }
+
+        /// Publishes the Merkle root of an airdrop too large to mint directly, or withdraws it
+        /// with None. Its leaves are hashes of (index, account, quantity); a new root starts a
+        /// new claim round
+        pub(external) fn set_claim_root(&mut self, root: Option<Hash>) -> Result<(), Error> {
+            diagnosed!(env, set_claim_root, {
+                if !self.is_minter(&env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+                let round = self.claim_round.checked_add(1).ok_or(Error::Overflow)?;
+
+                self.claim_root.set(root.map(|root| (root, env.caller())));
+                self.claim_round.set(round);
+                env.emit(EventClaimRootSet { caller: env.caller(), round: round, root: root });
+                Ok(())
+            })
+        }
+
+        /// Mints the caller's allocation at a leaf of the claim root, proven by the leaf's
+        /// Merkle proof; each leaf can be claimed once per round
+        pub(external) fn claim(&mut self, proof: Vec<Hash>, index: u64, quantity: TokenCount) -> Result<(u64, u64), Error> {
+            diagnosed!(env, claim, {
+                let claim_root = *self.claim_root;
+                if let None = claim_root {
+                    return Err(Error::NotFound);
+                }
+                let (root, minter) = claim_root.unwrap();
+
+                if self.is_claimed_impl(index) {
+                    return Err(Error::Duplicate);
+                }
+                let leaf = hash_of(&(index, env.caller(), quantity));
+                if !verify_merkle_proof(leaf, &proof, root) {
+                    return Err(Error::InvalidInput);
+                }
+                if !self.fits_public_supply(quantity) {
+                    return Err(Error::SupplyCapExceeded);
+                }
+
+                let (first_id, last_id) = self.mint_impl(minter, env.caller(), quantity, env.block_number())?;
+                let key = (*self.claim_round, index / 64);
+                let word = *self.claimed.get(&key).unwrap_or(&0);
+                self.claimed.insert(key, word | (1 << (index % 64)));
+
+                env.emit(EventClaimed { owner: env.caller(), index: index, quantity: quantity });
+                env.emit(EventMint { owner: env.caller(), value: quantity });
+                env.emit(EventConsecutiveTransfer { from_id: first_id, to_id: last_id, owner: env.caller() });
+                Ok((first_id, last_id))
+            })
+        }
+
+        /// Return whether mints, transfers and burns are halted
+        pub(external) fn paused(&self) -> bool {
+            *self.paused
+        }
+
+        /// Return the account able to move tokens by force while the contract is paused
+        pub(external) fn super_admin(&self) -> Option<AccountId> {
+            *self.super_admin
+        }
+
+        /// Halts or resumes mints, transfers and burns
+        pub(external) fn set_paused(&mut self, paused: bool) -> Result<(), Error> {
+            diagnosed!(env, set_paused, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                self.paused.set(paused);
+                env.emit(EventPaused { caller: env.caller(), paused: paused });
+                Ok(())
+            })
+        }
+
+        /// Sets, or removes with None, the account able to move tokens by force while paused
+        pub(external) fn set_super_admin(&mut self, super_admin: Option<AccountId>) -> Result<(), Error> {
+            diagnosed!(env, set_super_admin, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                self.super_admin.set(super_admin);
+                env.emit(EventSuperAdminSet { caller: env.caller(), super_admin: super_admin });
+                Ok(())
+            })
+        }
+
+        /// Moves a token to another account by force, such as under a court order; only the
+        /// super admin can do this, only while paused, and always with a recorded reason
+        pub(external) fn admin_transfer(&mut self, token_id: u64, to: AccountId, reason_hash: Hash) -> Result<(), Error> {
+            diagnosed!(env, admin_transfer, {
+                if *self.super_admin != Some(env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+                if *self.paused == false {
+                    return Err(Error::NotPaused);
+                }
+                if is_zero_account(&to) {
+                    return Err(Error::ZeroAccount);
+                }
+
+                let from = self.owner_of_impl(token_id);
+                if let None = from {
+                    return Err(Error::TokenNotFound);
+                }
+                let from = from.unwrap();
+                if from == to {
+                    return Err(Error::SelfTransfer);
+                }
+
+                // the contract is paused, so the transfer rules are checked past the pause; locks and
+                // cooldowns give way, bundles, nesting, custody and the holder cap do not
+                self.check_token_transfer(Some(from), Some(to), token_id, 1, env.block_number(), None, true)?;
+
+                let moved = self.move_token(from, to, token_id, env.block_number())?;
+
+                env.emit(EventAdminTransfer {
+                    admin: env.caller(),
+                    from: from,
+                    to: to,
+                    token_id: token_id,
+                    reason_hash: reason_hash,
+                    block: env.block_number(),
+                });
+                for moved_id in moved {
+                    env.emit(EventTransfer { from: from, to: to, token_id: moved_id });
+                }
+                Ok(())
+            })
+        }
+
+        /// Hands ownership of the contract, and with it every admin action, to another account
+        pub(external) fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), Error> {
+            diagnosed!(env, transfer_ownership, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+
+                self.owner.set(new_owner);
+                env.emit(EventOwnershipTransferred { previous_owner: env.caller(), new_owner: new_owner });
+                Ok(())
+            })
+        }
+
+        /// Return the most tokens that can ever be minted; 0 for no limit
+        pub(external) fn max_supply(&self) -> TokenCount {
+            *self.max_supply
+        }
+
+        /// Return the reserved tokens still to be minted through mint_reserved
+        pub(external) fn remaining_reserve(&self) -> TokenCount {
+            *self.reserved_remaining
+        }
+
+        /// Caps the supply and holds back part of it for mint_reserved; every other mint
+        /// has to leave the reserve untouched. The reserve is a number of tokens rather than
+        /// particular ids: ids are handed out in minting order, so reserved tokens take the next
+        /// ids when they are minted. An uncapped supply cannot have a reserve
+        pub(external) fn set_reserve(&mut self, max_supply: TokenCount, reserved: TokenCount) -> Result<(), Error> {
+            diagnosed!(env, set_reserve, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                if !self.is_valid_reserve(max_supply, reserved) {
+                    return Err(Error::InvalidInput);
+                }
+
+                self.max_supply.set(max_supply);
+                self.reserved_remaining.set(reserved);
+                env.emit(EventReserveSet { caller: env.caller(), max_supply: max_supply, reserved: reserved });
+                Ok(())
+            })
+        }
+
+        /// Mints tokens out of the reserve, returning the first and last token_id minted
+        pub(external) fn mint_reserved(&mut self, to: AccountId, value: TokenCount) -> Result<(u64, u64), Error> {
+            diagnosed!(env, mint_reserved, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                if value > *self.reserved_remaining {
+                    return Err(Error::SupplyCapExceeded);
+                }
+
+                let (first_id, last_id) = self.mint_impl(env.caller(), to, value, env.block_number())?;
+                self.reserved_remaining.set(*self.reserved_remaining - value);
+                env.emit(EventMint { owner: to, value: value });
+                env.emit(EventConsecutiveTransfer { from_id: first_id, to_id: last_id, owner: to });
+                Ok((first_id, last_id))
+            })
+        }
+
+        /// Mints tokens on the authority of a governance proposal, recording the proposal
+        /// against the batch; returns the first and last token_id minted
+        pub(external) fn mint_with_proposal_ref(&mut self, to: AccountId, value: TokenCount, proposal_id: u64) -> Result<(u64, u64), Error> {
+            diagnosed!(env, mint_with_proposal_ref, {
+                if !self.is_minter(&env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+                if !self.fits_public_supply(value) {
+                    return Err(Error::SupplyCapExceeded);
+                }
+
+                let (first_id, last_id) = self.mint_impl(env.caller(), to, value, env.block_number())?;
+                self.mint_provenance.insert(first_id, (env.caller(), proposal_id));
+                env.emit(EventMint { owner: to, value: value });
+                env.emit(EventConsecutiveTransfer { from_id: first_id, to_id: last_id, owner: to });
+                env.emit(EventMintAuthorized { minter: env.caller(), from_id: first_id, to_id: last_id, proposal_id: proposal_id });
+                Ok((first_id, last_id))
+            })
+        }
+
+        /// Return the most tokens a single account may hold; 0 for no limit
+        pub(external) fn max_per_holder(&self) -> TokenCount {
+            *self.max_per_holder
+        }
+
+        /// Caps the tokens a single account may receive through mints and transfers; 0 for no limit.
+        /// Accounts already above the cap keep their tokens
+        pub(external) fn set_max_per_holder(&mut self, max_per_holder: TokenCount) -> Result<(), Error> {
+            diagnosed!(env, set_max_per_holder, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                self.max_per_holder.set(max_per_holder);
+                env.emit(EventMaxPerHolderSet { caller: env.caller(), max_per_holder: max_per_holder });
+                Ok(())
+            })
+        }
+
+        /// Return the first block a token can be transferred in after its mint cooldown
+        pub(external) fn transferable_at(&self, token_id: u64) -> BlockNumber {
+            self.transferable_at_impl(token_id)
+        }
+
+        /// Sets the blocks a freshly minted token has to wait before it can be transferred
+        pub(external) fn set_transfer_cooldown(&mut self, cooldown: BlockNumber) -> Result<(), Error> {
+            diagnosed!(env, set_transfer_cooldown, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                self.transfer_cooldown.set(cooldown);
+                env.emit(EventTransferCooldownSet { caller: env.caller(), cooldown: cooldown });
+                Ok(())
+            })
+        }
+
+        /// Return the account that minted a token and the block it was minted in
+        pub(external) fn provenance_of(&self, token_id: u64) -> Option<(AccountId, BlockNumber)> {
+            self.batch_start_of(token_id)
+                .and_then(|start_id| self.mint_origins.get(&start_id).cloned())
+        }
+
+        /// Return who authorized the mint of a token and the proposal they referenced
+        pub(external) fn mint_authorization_of(&self, token_id: u64) -> Option<(AccountId, u64)> {
+            self.batch_start_of(token_id)
+                .and_then(|start_id| self.mint_provenance.get(&start_id).cloned())
+        }
+
+        /// Return the account minting in place of the owner, if one is set
+        pub(external) fn minter(&self) -> Option<AccountId> {
+            *self.minter
+        }
+
+        /// Hands the minting role to an account, such as a governance executor, or back to the owner with None
+        pub(external) fn set_minter(&mut self, minter: Option<AccountId>) -> Result<(), Error> {
+            diagnosed!(env, set_minter, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+
+                self.minter.set(minter);
+                env.emit(EventMinterSet { caller: env.caller(), minter: minter });
+                Ok(())
+            })
+        }
+
+        /// Mints tokens to a given address that cannot move before the unlock_at block,
+        /// returning the first and last token_id minted
+        pub(external) fn mint_locked(&mut self, to: AccountId, value: TokenCount, unlock_at: BlockNumber) -> Result<(u64, u64), Error> {
+            diagnosed!(env, mint_locked, {
+                if !self.is_minter(&env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+                if !self.fits_public_supply(value) {
+                    return Err(Error::SupplyCapExceeded);
+                }
+
+                let (first_id, last_id) = self.mint_impl(env.caller(), to, value, env.block_number())?;
+                // the lock is kept once for the whole batch
+                self.unlock_blocks.insert(first_id, unlock_at);
+                env.emit(EventMint { owner: to, value: value });
+                env.emit(EventConsecutiveTransfer { from_id: first_id, to_id: last_id, owner: to });
+                Ok((first_id, last_id))
+            })
+        }
+
+        /// Return the block a token becomes transferable at, or 0 if it was never locked
+        pub(external) fn unlock_block(&self, token_id: u64) -> BlockNumber {
+            let unlock_at = self.batch_start_of(token_id)
+                .and_then(|start_id| self.unlock_blocks.get(&start_id).cloned())
+                .unwrap_or(0);
+            unlock_at
+        }
+
+        /// Approves or disapproves an Account to send token on behalf of an owner
+        pub(external) fn approval(&mut self, to: AccountId, token_id: u64, approved: bool) -> Result<(), Error> {
+            diagnosed!(env, approval, {
+                self.approval_impl(env.caller(), to, token_id, approved)?;
+                env.emit(EventApproval { owner: env.caller(), spender: to, token_id: token_id, approved: approved });
+                Ok(())
+            })
+        }
+
+        /// Return a page of the approvals an owner has granted, as (token_id, spender)
+        pub(external) fn approvals_of(&self, owner: AccountId, start: u32, limit: u32) -> Vec<(u64, AccountId)> {
+            let token_ids = self.owner_approvals.get(&owner).cloned().unwrap_or_default();
+            token_ids
+                .into_iter()
+                .skip(start as usize)
+                .take(limit as usize)
+                .filter_map(|token_id| self.approvals.get(&token_id).map(|spender| (token_id, *spender)))
+                .collect()
+        }
+
+        /// Revokes the approvals of several of the caller's tokens at once
+        pub(external) fn revoke_approvals(&mut self, token_ids: Vec<u64>) -> Result<(), Error> {
+            diagnosed!(env, revoke_approvals, {
+                // check every token first so the revocation is all or nothing
+                for token_id in token_ids.iter() {
+                    if !self.is_token_owner(&env.caller(), *token_id) {
+                        return Err(Error::NotOwner);
+                    }
+                }
+
+                for token_id in token_ids {
+                    if let Some(spender) = self.approvals.get(&token_id).cloned() {
+                        self.clear_approval(env.caller(), token_id);
+                        env.emit(EventApproval { owner: env.caller(), spender: spender, token_id: token_id, approved: false });
+                    }
+                }
+                Ok(())
+            })
+        }
+
+        /// Returns whether an operator may move every token of an owner
+        pub(external) fn is_approved_for_all(&self, owner: AccountId, operator: AccountId) -> bool {
+            let approved = *self.operator_approvals.get(&(owner, operator)).unwrap_or(&false);
+            approved
+        }
+
+        /// Return the (spender, from_id, to_id) ranges an owner has approved
+        pub(external) fn range_approvals_of(&self, owner: AccountId) -> Vec<(AccountId, u64, u64)> {
+            self.range_approvals.get(&owner).cloned().unwrap_or_default()
+        }
+
+        /// Approves a spender for every token the caller holds in a contiguous range of ids.
+        /// The approval covers tokens while the caller holds them, not tokens they hand on
+        pub(external) fn approve_range(&mut self, spender: AccountId, from_id: u64, to_id: u64) -> Result<(), Error> {
+            diagnosed!(env, approve_range, {
+                if spender == env.caller() {
+                    return Err(Error::SelfApproval);
+                }
+                if is_zero_account(&spender) {
+                    return Err(Error::ZeroAccount);
+                }
+                if from_id == 0 || from_id > to_id {
+                    return Err(Error::InvalidInput);
+                }
+                if !self.is_allowed_operator(&spender) {
+                    return Err(Error::Unauthorized);
+                }
+
+                let mut ranges = self.range_approvals.get(&env.caller()).cloned().unwrap_or_default();
+                if ranges.contains(&(spender, from_id, to_id)) {
+                    return Err(Error::Duplicate);
+                }
+                if ranges.len() >= MAX_RANGE_APPROVALS {
+                    return Err(Error::LimitReached);
+                }
+                ranges.push((spender, from_id, to_id));
+                self.range_approvals.insert(env.caller(), ranges);
+
+                env.emit(EventRangeApproval { owner: env.caller(), spender: spender, from_id: from_id, to_id: to_id, approved: true });
+                Ok(())
+            })
+        }
+
+        /// Revokes a range approval given with approve_range
+        pub(external) fn revoke_range(&mut self, spender: AccountId, from_id: u64, to_id: u64) -> Result<(), Error> {
+            diagnosed!(env, revoke_range, {
+                let mut ranges = self.range_approvals.get(&env.caller()).cloned().unwrap_or_default();
+                let position = ranges.iter().position(|range| *range == (spender, from_id, to_id));
+                if let None = position {
+                    return Err(Error::NotFound);
+                }
+                ranges.remove(position.unwrap());
+                self.range_approvals.insert(env.caller(), ranges);
+
+                env.emit(EventRangeApproval { owner: env.caller(), spender: spender, from_id: from_id, to_id: to_id, approved: false });
+                Ok(())
+            })
+        }
+
+        /// Return how many more transfers an operator may make for an owner through its allowance
+        pub(external) fn remaining_allowance(&self, owner: AccountId, operator: AccountId) -> u32 {
+            let remaining = *self.operator_allowances.get(&(owner, operator)).unwrap_or(&0);
+            remaining
+        }
+
+        /// Lets an operator transfer up to the given number of the caller's tokens; 0 revokes the allowance
+        pub(external) fn approve_transfers(&mut self, operator: AccountId, transfers: u32) -> Result<(), Error> {
+            diagnosed!(env, approve_transfers, {
+                if operator == env.caller() {
+                    return Err(Error::SelfApproval);
+                }
+                if is_zero_account(&operator) {
+                    return Err(Error::ZeroAccount);
+                }
+                if transfers > 0 && !self.is_allowed_operator(&operator) {
+                    return Err(Error::Unauthorized);
+                }
+
+                if transfers > 0 {
+                    self.operator_allowances.insert((env.caller(), operator), transfers);
+                } else {
+                    self.operator_allowances.remove(&(env.caller(), operator));
+                }
+
+                env.emit(EventOperatorAllowance { owner: env.caller(), operator: operator, transfers: transfers });
+                Ok(())
+            })
+        }
+
+        /// Return whether operators have to be registered to be approved for all
+        pub(external) fn operator_filter(&self) -> bool {
+            *self.operator_filter
+        }
+
+        /// Return whether an operator is registered with the operator filter
+        pub(external) fn is_registered_operator(&self, operator: AccountId) -> bool {
+            let registered = *self.registered_operators.get(&operator).unwrap_or(&false);
+            registered
+        }
+
+        /// Turns the operator filter on or off
+        pub(external) fn set_operator_filter(&mut self, enabled: bool) -> Result<(), Error> {
+            diagnosed!(env, set_operator_filter, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                self.operator_filter.set(enabled);
+                env.emit(EventOperatorFilterSet { caller: env.caller(), enabled: enabled });
+                Ok(())
+            })
+        }
+
+        /// Registers or removes an operator, such as a marketplace, with the operator filter
+        pub(external) fn register_operator(&mut self, operator: AccountId, registered: bool) -> Result<(), Error> {
+            diagnosed!(env, register_operator, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                if registered == true {
+                    self.registered_operators.insert(operator, true);
+                } else {
+                    self.registered_operators.remove(&operator);
+                }
+                env.emit(EventOperatorRegistered { caller: env.caller(), operator: operator, registered: registered });
+                Ok(())
+            })
+        }
+
+        /// Approves or disapproves an operator to send any of the caller's tokens
+        pub(external) fn set_approval_for_all(&mut self, operator: AccountId, approved: bool) -> Result<(), Error> {
+            diagnosed!(env, set_approval_for_all, {
+                self.set_approval_for_all_impl(env.caller(), operator, approved)?;
+                env.emit(EventApprovalForAll { owner: env.caller(), operator: operator, approved: approved });
+                Ok(())
+            })
+        }
+
+        /// Return the bundle a token belongs to, or 0 if it is not bundled
+        pub(external) fn bundle_of(&self, token_id: u64) -> u64 {
+            let bundle_id = *self.token_to_bundle.get(&token_id).unwrap_or(&0);
+            bundle_id
+        }
+
+        /// Return the token_ids grouped in a bundle
+        pub(external) fn tokens_in_bundle(&self, bundle_id: u64) -> Vec<u64> {
+            self.bundles.get(&bundle_id).cloned().unwrap_or_default()
+        }
+
+        /// Groups several of the caller's tokens into a bundle that moves as one unit
+        pub(external) fn create_bundle(&mut self, token_ids: Vec<u64>) -> Result<(), Error> {
+            diagnosed!(env, create_bundle, {
+                // a bundle needs at least two tokens
+                if token_ids.len() < 2 {
+                    return Err(Error::InvalidInput);
+                }
+
+                // every token must be owned by the caller, unbundled, unnested and listed once
+                for (i, token_id) in token_ids.iter().enumerate() {
+                    if !self.is_token_owner(&env.caller(), *token_id) {
+                        return Err(Error::NotOwner);
+                    }
+                    if self.token_to_bundle.get(token_id).is_some() {
+                        return Err(Error::TokenBundled);
+                    }
+                    // a nested token moves with its parent, so a bundle could not take it elsewhere
+                    if self.parents.get(token_id).is_some() {
+                        return Err(Error::TokenNested);
+                    }
+                    if self.is_locked(*token_id, env.block_number()) {
+                        return Err(Error::TokenLocked);
+                    }
+                    if self.is_in_custody(*token_id) {
+                        return Err(Error::TokenInCustody);
+                    }
+                    if token_ids[..i].contains(token_id) {
+                        return Err(Error::Duplicate);
+                    }
+                }
+
+                let bundle_id = *self.total_bundles + 1;
+                for token_id in token_ids.iter() {
+                    self.token_to_bundle.insert(*token_id, bundle_id);
+                }
+                self.bundles.insert(bundle_id, token_ids);
+                self.total_bundles += 1;
+
+                env.emit(EventBundleCreated { owner: env.caller(), bundle_id: bundle_id });
+                Ok(())
+            })
+        }
+
+        /// Transfers every token of a bundle from the caller to a specified address
+        pub(external) fn transfer_bundle(&mut self, to: AccountId, bundle_id: u64) -> Result<(), Error> {
+            diagnosed!(env, transfer_bundle, {
+                let moved = self.transfer_bundle_impl(env.caller(), to, bundle_id, env.block_number())?;
+                env.emit(EventBundleTransfer { from: env.caller(), to: to, bundle_id: bundle_id });
+                for moved_id in moved {
+                    env.emit(EventTransfer { from: env.caller(), to: to, token_id: moved_id });
+                }
+                Ok(())
+            })
+        }
+
+        /// Dissolves a bundle, releasing its tokens to be moved individually again
+        pub(external) fn dissolve_bundle(&mut self, bundle_id: u64) -> Result<(), Error> {
+            diagnosed!(env, dissolve_bundle, {
+                if !self.is_bundle_owner(&env.caller(), bundle_id) {
+                    return Err(Error::NotOwner);
+                }
+
+                let token_ids = self.bundles.remove(&bundle_id).unwrap_or_default();
+                for token_id in token_ids.iter() {
+                    self.token_to_bundle.remove(token_id);
+                }
+
+                env.emit(EventBundleDissolved { owner: env.caller(), bundle_id: bundle_id });
+                Ok(())
+            })
+        }
+
+        /// Return the current user of a token, if the user role has not expired
+        pub(external) fn user_of(&self, token_id: u64) -> Option<AccountId> {
+            let user = self.users.get(&token_id);
+            if let None = user {
+                return None;
+            }
+            let (user, expires) = *user.unwrap();
+            if env.block_number() >= expires {
+                return None;
+            }
+            Some(user)
+        }
+
+        /// Return the block at which the user role of a token expires, or 0 if none is set
+        pub(external) fn user_expires(&self, token_id: u64) -> BlockNumber {
+            let expires = self.users.get(&token_id).map(|(_, expires)| *expires).unwrap_or(0);
+            expires
+        }
+
+        /// Grants a temporary user role on a token until the given block
+        pub(external) fn set_user(&mut self, token_id: u64, user: AccountId, expires: BlockNumber) -> Result<(), Error> {
+            diagnosed!(env, set_user, {
+                // only the token owner can lend out its use
+                if !self.is_token_owner(&env.caller(), token_id) {
+                    return Err(Error::NotOwner);
+                }
+
+                // the role must expire in the future
+                if expires <= env.block_number() {
+                    return Err(Error::Expired);
+                }
+
+                self.users.insert(token_id, (user, expires));
+                env.emit(EventUpdateUser { token_id: token_id, user: user, expires: expires });
+                Ok(())
+            })
+        }
+
+        /// Returns whether a token is still valid; tokens without an expiry never lapse
+        pub(external) fn is_valid(&self, token_id: u64) -> bool {
+            if let None = self.owner_of_impl(token_id) {
+                return false;
+            }
+            let expires_at = self.expirations.get(&token_id);
+            if let None = expires_at {
+                return true;
+            }
+            env.now() < *expires_at.unwrap()
+        }
+
+        /// Return the time a token expires at, or 0 if it never expires
+        pub(external) fn expires_at(&self, token_id: u64) -> Moment {
+            let expires_at = *self.expirations.get(&token_id).unwrap_or(&0);
+            expires_at
+        }
+
+        /// Return the time a renewal extends a token by
+        pub(external) fn renewal_period(&self) -> Moment {
+            let renewal_period = *self.renewal_period;
+            renewal_period
+        }
+
+        /// Sets the expiry of a token, turning it into a subscription token
+        pub(external) fn set_expiry(&mut self, token_id: u64, expires_at: Moment) -> Result<(), Error> {
+            diagnosed!(env, set_expiry, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                if let None = self.owner_of_impl(token_id) {
+                    return Err(Error::TokenNotFound);
+                }
+
+                self.expirations.insert(token_id, expires_at);
+                env.emit(EventExpiryUpdated { caller: env.caller(), token_id: token_id, expires_at: expires_at });
+                Ok(())
+            })
+        }
+
+        /// Sets the time a renewal extends a token by; 0 disables renewals
+        pub(external) fn set_renewal_period(&mut self, period: Moment) -> Result<(), Error> {
+            diagnosed!(env, set_renewal_period, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                self.renewal_period.set(period);
+                env.emit(EventRenewalPeriodSet { caller: env.caller(), period: period });
+                Ok(())
+            })
+        }
+
+        /// Renews a subscription token for another renewal period; the caller has to hold the
+        /// token or be approved for it
+        pub(external) fn renew(&mut self, token_id: u64) -> Result<(), Error> {
+            diagnosed!(env, renew, {
+                let owner = self.owner_of_impl(token_id);
+                if let None = owner {
+                    return Err(Error::TokenNotFound);
+                }
+                if !self.is_authorized(&env.caller(), &owner.unwrap(), token_id) {
+                    return Err(Error::NotApproved);
+                }
+
+                // only subscription tokens can be renewed, and only while renewals are enabled
+                let expires_at = self.expirations.get(&token_id);
+                if let None = expires_at {
+                    return Err(Error::InvalidInput);
+                }
+                if *self.renewal_period == 0 {
+                    return Err(Error::InvalidInput);
+                }
+
+                // lapsed tokens are renewed from now rather than from their old expiry
+                let expires_at = *expires_at.unwrap();
+                let start = if expires_at > env.now() { expires_at } else { env.now() };
+                let expires_at = start.checked_add(*self.renewal_period).ok_or(Error::Overflow)?;
+
+                self.expirations.insert(token_id, expires_at);
+                env.emit(EventExpiryUpdated { caller: env.caller(), token_id: token_id, expires_at: expires_at });
+                Ok(())
+            })
+        }
+
+        /// Return the token a token is nested in, or 0 if it is not nested
+        pub(external) fn parent_of(&self, token_id: u64) -> u64 {
+            let parent_id = *self.parents.get(&token_id).unwrap_or(&0);
+            parent_id
+        }
+
+        /// Return the tokens nested directly inside a token
+        pub(external) fn children_of(&self, token_id: u64) -> Vec<u64> {
+            self.children.get(&token_id).cloned().unwrap_or_default()
+        }
+
+        /// Nests one of the caller's tokens inside another, so it follows the parent on transfer
+        pub(external) fn nest(&mut self, child_id: u64, parent_id: u64) -> Result<(), Error> {
+            diagnosed!(env, nest, {
+                if child_id == parent_id {
+                    return Err(Error::InvalidInput);
+                }
+                if !self.is_token_owner(&env.caller(), child_id) || !self.is_token_owner(&env.caller(), parent_id) {
+                    return Err(Error::NotOwner);
+                }
+
+                // the child must be free to move on its own
+                if self.parents.get(&child_id).is_some() {
+                    return Err(Error::TokenNested);
+                }
+                if self.token_to_bundle.get(&child_id).is_some() {
+                    return Err(Error::TokenBundled);
+                }
+                if self.is_locked(child_id, env.block_number()) {
+                    return Err(Error::TokenLocked);
+                }
+                if self.is_in_custody(child_id) {
+                    return Err(Error::TokenInCustody);
+                }
+
+                // a token cannot be nested inside one of its own descendants
+                if self.is_descendant_of(parent_id, child_id) {
+                    return Err(Error::InvalidInput);
+                }
+
+                // nor inside a bundled token, whose bundle would carry it along
+                if self.token_to_bundle.get(&parent_id).is_some() {
+                    return Err(Error::TokenBundled);
+                }
+
+                self.parents.insert(child_id, parent_id);
+                let mut children = self.children.get(&parent_id).cloned().unwrap_or_default();
+                children.push(child_id);
+                self.children.insert(parent_id, children);
+
+                env.emit(EventNest { owner: env.caller(), parent_id: parent_id, child_id: child_id });
+                Ok(())
+            })
+        }
+
+        /// Ejects a nested token out of its parent, leaving it with the parent's owner
+        pub(external) fn eject_child(&mut self, parent_id: u64, child_id: u64) -> Result<(), Error> {
+            diagnosed!(env, eject_child, {
+                if !self.is_token_owner(&env.caller(), parent_id) {
+                    return Err(Error::NotOwner);
+                }
+                if *self.parents.get(&child_id).unwrap_or(&0) != parent_id {
+                    return Err(Error::InvalidInput);
+                }
+
+                // equipped children have to be unequipped first
+                if self.equipped_slots.get(&child_id).is_some() {
+                    return Err(Error::InvalidInput);
+                }
+
+                self.parents.remove(&child_id);
+                let mut children = self.children.get(&parent_id).cloned().unwrap_or_default();
+                children.retain(|id| *id != child_id);
+                self.children.insert(parent_id, children);
+
+                env.emit(EventEject { owner: env.caller(), parent_id: parent_id, child_id: child_id });
+                Ok(())
+            })
+        }
+
+        /// Return whether a token is allowed in the given slot
+        pub(external) fn is_equippable(&self, slot: Vec<u8>, token_id: u64) -> bool {
+            let allowed = *self.slot_rules.get(&(slot, token_id)).unwrap_or(&false);
+            allowed
+        }
+
+        /// Return the token equipped in a slot of a parent token, or 0 if the slot is empty
+        pub(external) fn equipped_in(&self, parent_id: u64, slot: Vec<u8>) -> u64 {
+            let child_id = *self.equipped.get(&(parent_id, slot)).unwrap_or(&0);
+            child_id
+        }
+
+        /// Allows or disallows a token to be equipped in a slot
+        pub(external) fn set_slot_rule(&mut self, slot: Vec<u8>, token_id: u64, allowed: bool) -> Result<(), Error> {
+            diagnosed!(env, set_slot_rule, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+
+                if allowed == true {
+                    self.slot_rules.insert((slot.clone(), token_id), true);
+                } else {
+                    self.slot_rules.remove(&(slot.clone(), token_id));
+                }
+
+                env.emit(EventSlotRule { caller: env.caller(), slot: slot, token_id: token_id, allowed: allowed });
+                Ok(())
+            })
+        }
+
+        /// Equips a child token nested in a parent into one of the parent's slots
+        pub(external) fn equip(&mut self, parent_id: u64, slot: Vec<u8>, child_id: u64) -> Result<(), Error> {
+            diagnosed!(env, equip, {
+                if !self.is_token_owner(&env.caller(), parent_id) {
+                    return Err(Error::NotOwner);
+                }
+
+                // the child must be nested directly in the parent and fit the slot
+                if *self.parents.get(&child_id).unwrap_or(&0) != parent_id {
+                    return Err(Error::InvalidInput);
+                }
+                if !*self.slot_rules.get(&(slot.clone(), child_id)).unwrap_or(&false) {
+                    return Err(Error::Unauthorized);
+                }
+
+                // neither the slot nor the child may already be in use
+                if self.equipped.get(&(parent_id, slot.clone())).is_some() {
+                    return Err(Error::Duplicate);
+                }
+                if self.equipped_slots.get(&child_id).is_some() {
+                    return Err(Error::Duplicate);
+                }
+
+                self.equipped.insert((parent_id, slot.clone()), child_id);
+                self.equipped_slots.insert(child_id, slot.clone());
+
+                env.emit(EventEquip { parent_id: parent_id, slot: slot, child_id: child_id });
+                Ok(())
+            })
+        }
+
+        /// Unequips whatever token is held in a slot of a parent token
+        pub(external) fn unequip(&mut self, parent_id: u64, slot: Vec<u8>) -> Result<(), Error> {
+            diagnosed!(env, unequip, {
+                if !self.is_token_owner(&env.caller(), parent_id) {
+                    return Err(Error::NotOwner);
+                }
+
+                let child_id = self.equipped.remove(&(parent_id, slot.clone()));
+                if let None = child_id {
+                    return Err(Error::NotFound);
+                }
+                let child_id = child_id.unwrap();
+                self.equipped_slots.remove(&child_id);
+
+                env.emit(EventUnequip { parent_id: parent_id, slot: slot, child_id: child_id });
+                Ok(())
+            })
+        }
+
+        /// Return the accepted assets of a token as (priority, uri), highest priority (lowest value) first
+        pub(external) fn assets_of(&self, token_id: u64) -> Vec<(u32, Vec<u8>)> {
+            self.assets.get(&token_id).cloned().unwrap_or_default()
+        }
+
+        /// Return the assets proposed for a token that await the holder's decision
+        pub(external) fn pending_assets_of(&self, token_id: u64) -> Vec<(u32, Vec<u8>)> {
+            self.pending_assets.get(&token_id).cloned().unwrap_or_default()
+        }
+
+        /// Return the oracle account and the blocks it waits between updates of a token
+        pub(external) fn oracle(&self) -> (Option<AccountId>, BlockNumber) {
+            (*self.oracle, *self.oracle_interval)
+        }
+
+        /// Return whether the oracle may update a token's attributes
+        pub(external) fn is_dynamic(&self, token_id: u64) -> bool {
+            let dynamic = *self.dynamic_tokens.get(&token_id).unwrap_or(&false);
+            dynamic
+        }
+
+        /// Return the value of a token attribute; empty if it was never set
+        pub(external) fn attribute_of(&self, token_id: u64, key: Vec<u8>) -> Vec<u8> {
+            self.attributes.get(&(token_id, key)).cloned().unwrap_or_default()
+        }
+
+        /// Sets the account that pushes attribute updates and how often it may update a token
+        pub(external) fn set_oracle(&mut self, oracle: Option<AccountId>, interval: BlockNumber) -> Result<(), Error> {
+            diagnosed!(env, set_oracle, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                self.oracle.set(oracle);
+                self.oracle_interval.set(interval);
+                env.emit(EventOracleSet { caller: env.caller(), oracle: oracle, interval: interval });
+                Ok(())
+            })
+        }
+
+        /// Flags a token as dynamic, letting the oracle update its attributes
+        pub(external) fn set_dynamic(&mut self, token_id: u64, dynamic: bool) -> Result<(), Error> {
+            diagnosed!(env, set_dynamic, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                if let None = self.owner_of_impl(token_id) {
+                    return Err(Error::TokenNotFound);
+                }
+                self.dynamic_tokens.insert(token_id, dynamic);
+                env.emit(EventDynamicSet { caller: env.caller(), token_id: token_id, dynamic: dynamic });
+                Ok(())
+            })
+        }
+
+        /// Pushes (key, value) attribute updates for a dynamic token; only the oracle
+        /// can call it, at most once per interval for each token
+        pub(external) fn push_attributes(&mut self, token_id: u64, updates: Vec<(Vec<u8>, Vec<u8>)>) -> Result<(), Error> {
+            diagnosed!(env, push_attributes, {
+                if *self.oracle != Some(env.caller()) {
+                    return Err(Error::Unauthorized);
+                }
+                if !*self.dynamic_tokens.get(&token_id).unwrap_or(&false) {
+                    return Err(Error::InvalidInput);
+                }
+                if let Some(updated_at) = self.oracle_updated_at.get(&token_id) {
+                    if env.block_number() < updated_at.saturating_add(*self.oracle_interval) {
+                        return Err(Error::NotReady);
+                    }
+                }
+
+                let mut keys = Vec::new();
+                for (key, value) in updates {
+                    keys.push(key.clone());
+                    self.attributes.insert((token_id, key), value);
+                }
+                self.oracle_updated_at.insert(token_id, env.block_number());
+
+                env.emit(EventAttributesUpdated { token_id: token_id, keys: keys });
+                Ok(())
+            })
+        }
+
+        /// Return the URI schemes accepted for asset URIs besides `ipfs://<CID>`
+        pub(external) fn allowed_uri_schemes(&self) -> Vec<Vec<u8>> {
+            (*self.allowed_uri_schemes).clone()
+        }
+
+        /// Sets the URI schemes, such as `ar`, accepted for asset URIs besides `ipfs://<CID>`
+        pub(external) fn set_allowed_uri_schemes(&mut self, schemes: Vec<Vec<u8>>) -> Result<(), Error> {
+            diagnosed!(env, set_allowed_uri_schemes, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                if !is_valid_schemes(&schemes) {
+                    return Err(Error::InvalidInput);
+                }
+
+                self.allowed_uri_schemes.set(schemes.clone());
+                env.emit(EventUriSchemesSet { caller: env.caller(), schemes: schemes });
+                Ok(())
+            })
+        }
+
+        /// Proposes a new asset for a token, to be accepted or rejected by its holder
+        pub(external) fn propose_asset(&mut self, token_id: u64, uri: Vec<u8>, priority: u32) -> Result<(), Error> {
+            diagnosed!(env, propose_asset, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                if let None = self.owner_of_impl(token_id) {
+                    return Err(Error::TokenNotFound);
+                }
+                if !self.is_valid_uri(&uri) {
+                    return Err(Error::InvalidUri);
+                }
+
+                let mut pending = self.pending_assets.get(&token_id).cloned().unwrap_or_default();
+                pending.push((priority, uri.clone()));
+                self.pending_assets.insert(token_id, pending);
+
+                env.emit(EventAssetProposed { token_id: token_id, priority: priority, uri: uri });
+                Ok(())
+            })
+        }
+
+        /// Accepts the proposed asset at the given index of the pending list
+        pub(external) fn accept_asset(&mut self, token_id: u64, index: u32) -> Result<(), Error> {
+            diagnosed!(env, accept_asset, {
+                if !self.is_token_owner(&env.caller(), token_id) {
+                    return Err(Error::NotOwner);
+                }
+
+                let mut pending = self.pending_assets.get(&token_id).cloned().unwrap_or_default();
+                if index as usize >= pending.len() {
+                    return Err(Error::InvalidInput);
+                }
+                let (priority, uri) = pending.remove(index as usize);
+                self.pending_assets.insert(token_id, pending);
+
+                // keep accepted assets ordered by priority
+                let mut assets = self.assets.get(&token_id).cloned().unwrap_or_default();
+                let position = assets.iter().position(|(p, _)| *p > priority).unwrap_or(assets.len());
+                assets.insert(position, (priority, uri.clone()));
+                self.assets.insert(token_id, assets);
+
+                env.emit(EventAssetAccepted { token_id: token_id, priority: priority, uri: uri });
+                Ok(())
+            })
+        }
+
+        /// Rejects the proposed asset at the given index of the pending list
+        pub(external) fn reject_asset(&mut self, token_id: u64, index: u32) -> Result<(), Error> {
+            diagnosed!(env, reject_asset, {
+                if !self.is_token_owner(&env.caller(), token_id) {
+                    return Err(Error::NotOwner);
+                }
+
+                let mut pending = self.pending_assets.get(&token_id).cloned().unwrap_or_default();
+                if index as usize >= pending.len() {
+                    return Err(Error::InvalidInput);
+                }
+                let (_, uri) = pending.remove(index as usize);
+                self.pending_assets.insert(token_id, pending);
+
+                env.emit(EventAssetRejected { token_id: token_id, uri: uri });
+                Ok(())
+            })
+        }
+
+        /// Return the genes of a token; tokens that were not fused have none
+        pub(external) fn genes_of(&self, token_id: u64) -> u64 {
+            let genes = *self.genes.get(&token_id).unwrap_or(&0);
+            genes
+        }
+
+        /// Return the block a token can be fused from
+        pub(external) fn fusion_ready_at(&self, token_id: u64) -> BlockNumber {
+            let ready_at = *self.fusion_ready_at.get(&token_id).unwrap_or(&0);
+            ready_at
+        }
+
+        /// Sets the blocks a freshly fused token has to wait before it can be fused again
+        pub(external) fn set_fusion_cooldown(&mut self, cooldown: BlockNumber) -> Result<(), Error> {
+            diagnosed!(env, set_fusion_cooldown, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+                // with a timelock in place the change has to be scheduled
+                if *self.timelock_delay > 0 {
+                    return Err(Error::Timelocked);
+                }
+                self.fusion_cooldown.set(cooldown);
+                env.emit(EventFusionCooldownSet { caller: env.caller(), cooldown: cooldown });
+                Ok(())
+            })
+        }
+
+        /// Burns two of the caller's tokens and mints a child whose genes derive from both parents
+        pub(external) fn fuse(&mut self, token_a: u64, token_b: u64) -> Result<(), Error> {
+            diagnosed!(env, fuse, {
+                if token_a == token_b {
+                    return Err(Error::InvalidInput);
+                }
+                if !self.is_token_owner(&env.caller(), token_a) || !self.is_token_owner(&env.caller(), token_b) {
+                    return Err(Error::NotOwner);
+                }
+
+                // both parents must be out of their cooldown
+                let block = env.block_number();
+                let ready_a = *self.fusion_ready_at.get(&token_a).unwrap_or(&0);
+                let ready_b = *self.fusion_ready_at.get(&token_b).unwrap_or(&0);
+                if block < ready_a || block < ready_b {
+                    return Err(Error::NotReady);
+                }
+
+                // check both parents can be burned before touching either of them
+                self.before_token_transfer(Some(env.caller()), None, token_a, 1, block, None)?;
+                self.before_token_transfer(Some(env.caller()), None, token_b, 1, block, None)?;
+                if !self.fits_public_supply(1) {
+                    return Err(Error::SupplyCapExceeded);
+                }
+                // the child arrives once both parents are gone, which a lowered cap may still not allow
+                let remaining = self.owner_to_token_count.get(&env.caller()).unwrap_or(&0).saturating_sub(2);
+                if *self.max_per_holder > 0 && remaining.saturating_add(1) > *self.max_per_holder {
+                    return Err(Error::HolderCapExceeded);
+                }
+                // the child is minted after the parents are burned, so it must be able to get an id
+                if self.total_minted.checked_add(1).is_none() {
+                    return Err(Error::Overflow);
+                }
+
+                let seed = env.random_seed();
+                let mut entropy = [0u8; 8];
+                entropy.copy_from_slice(&seed.as_ref()[..8]);
+                let genes_a = *self.genes.get(&token_a).unwrap_or(&0);
+                let genes_b = *self.genes.get(&token_b).unwrap_or(&0);
+                let genes = genes_a ^ genes_b.rotate_left(32) ^ u64::from_le_bytes(entropy);
+
+                self.burn_impl(env.caller(), token_a, block)?;
+                self.burn_impl(env.caller(), token_b, block)?;
+                env.emit(EventBurn { owner: env.caller(), token_id: token_a });
+                env.emit(EventBurn { owner: env.caller(), token_id: token_b });
+
+                let (child_id, _) = self.mint_impl(env.caller(), env.caller(), 1, block)?;
+                env.emit(EventConsecutiveTransfer { from_id: child_id, to_id: child_id, owner: env.caller() });
+                self.genes.insert(child_id, genes);
+                self.fusion_ready_at.insert(child_id, block.saturating_add(*self.fusion_cooldown));
+
+                env.emit(EventFuse { owner: env.caller(), token_a: token_a, token_b: token_b, child_id: child_id, genes: genes });
+                Ok(())
+            })
+        }
+
+        /// Return whether a redemption campaign is open
+        pub(external) fn is_campaign_open(&self, campaign_id: u64) -> bool {
+            let open = self.campaigns.get(&campaign_id).map(|(_, open)| *open).unwrap_or(false);
+            open
+        }
+
+        /// Return whether a token has been redeemed in a campaign
+        pub(external) fn is_redeemed(&self, token_id: u64, campaign_id: u64) -> bool {
+            self.redemptions.get(&(campaign_id, token_id)).is_some()
+        }
+
+        /// Opens a new redemption campaign, that either burns or flags redeemed tokens
+        pub(external) fn create_campaign(&mut self, burns: bool) -> Result<(), Error> {
+            diagnosed!(env, create_campaign, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+
+                let campaign_id = *self.total_campaigns + 1;
+                self.campaigns.insert(campaign_id, (burns, true));
+                self.total_campaigns += 1;
+
+                env.emit(EventCampaignCreated { caller: env.caller(), campaign_id: campaign_id, burns: burns });
+                Ok(())
+            })
+        }
+
+        /// Closes a redemption campaign to further redemptions
+        pub(external) fn close_campaign(&mut self, campaign_id: u64) -> Result<(), Error> {
+            diagnosed!(env, close_campaign, {
+                if env.caller() != *self.owner {
+                    return Err(Error::Unauthorized);
+                }
+
+                let campaign = self.campaigns.get(&campaign_id);
+                if let None = campaign {
+                    return Err(Error::NotFound);
+                }
+                let (burns, _) = *campaign.unwrap();
+                self.campaigns.insert(campaign_id, (burns, false));
+
+                env.emit(EventCampaignClosed { caller: env.caller(), campaign_id: campaign_id });
+                Ok(())
+            })
+        }
+
+        /// Return the claim hash a token was redeemed with in a campaign
+        pub(external) fn redemption_of(&self, token_id: u64, campaign_id: u64) -> Option<Hash> {
+            self.redemptions.get(&(campaign_id, token_id)).cloned()
+        }
+
+        /// Redeems a token in a campaign. The claim hash fulfilment matches the redemption by is
+        /// the hash of (campaign_id, token_id, owner, block), computed here so holders cannot
+        /// choose it
+        pub(external) fn redeem(&mut self, token_id: u64, campaign_id: u64) -> Result<(), Error> {
+            diagnosed!(env, redeem, {
+                if !self.is_token_owner(&env.caller(), token_id) {
+                    return Err(Error::NotOwner);
+                }
+
+                let campaign = self.campaigns.get(&campaign_id);
+                if let None = campaign {
+                    return Err(Error::NotFound);
+                }
+                let (burns, open) = *campaign.unwrap();
+                if open == false {
+                    return Err(Error::Expired);
+                }
+
+                // a token can only be redeemed once per campaign
+                if self.redemptions.get(&(campaign_id, token_id)).is_some() {
+                    return Err(Error::Duplicate);
+                }
+
+                if burns == true {
+                    self.burn_impl(env.caller(), token_id, env.block_number())?;
+                    env.emit(EventBurn { owner: env.caller(), token_id: token_id });
+                }
+                let claim_hash = hash_of(&(campaign_id, token_id, env.caller(), env.block_number()));
+                self.redemptions.insert((campaign_id, token_id), claim_hash);
+
+                env.emit(EventRedeemed { owner: env.caller(), token_id: token_id, campaign_id: campaign_id, claim_hash: claim_hash });
+                Ok(())
+            })
+        }
+
+        /// Return the token registered under a handle, or 0 if the handle is free
+        pub(external) fn token_id_of(&self, handle: Vec<u8>) -> u64 {
+            let token_id = *self.handles.get(&handle).unwrap_or(&0);
+            token_id
+        }
+
+        /// Return the handle of a token, or an empty handle if it has none
+        pub(external) fn handle_of(&self, token_id: u64) -> Vec<u8> {
+            self.token_handles.get(&token_id).cloned().unwrap_or_default()
+        }
+
+        /// Assigns a unique handle to one of the caller's tokens; an empty handle clears it
+        pub(external) fn set_handle(&mut self, token_id: u64, handle: Vec<u8>) -> Result<(), Error> {
+            diagnosed!(env, set_handle, {
+                if !self.is_token_owner(&env.caller(), token_id) {
+                    return Err(Error::NotOwner);
+                }
+
+                // handles are unique across the collection
+                if !handle.is_empty() && self.handles.get(&handle).is_some() {
+                    return Err(Error::Duplicate);
+                }
+
+                self.clear_handle(token_id);
+                if !handle.is_empty() {
+                    self.handles.insert(handle.clone(), token_id);
+                    self.token_handles.insert(token_id, handle.clone());
+                }
+
+                env.emit(EventHandleSet { token_id: token_id, handle: handle });
+                Ok(())
+            })
+        }
+    }
+
+    /// Private Methods
+    impl NFToken {
+
+        /// Resolves the owner of a token from its own ownership entry, or else from its mint batch
+        fn owner_of_impl(&self, token_id: u64) -> Option<AccountId> {
+            if token_id == 0 || token_id > *self.total_minted {
+                return None;
+            }
+            if self.burned.get(&token_id).is_some() {
+                return None;
+            }
+            if let Some(owner) = self.id_to_owner.get(&token_id) {
+                return Some(*owner);
+            }
+
+            // a token that never changed hands still belongs to whoever received its batch
+            self.batch_start_of(token_id)
+                .and_then(|start_id| self.mint_batches.get(&start_id))
+                .map(|(_, receiver, _)| *receiver)
+        }
+
+        /// Return the first token_id of the mint batch a token belongs to, by binary search
+        /// over the batch starts, which are recorded in increasing order
+        fn batch_start_of(&self, token_id: u64) -> Option<u64> {
+            if token_id == 0 || token_id > *self.total_minted {
+                return None;
+            }
+
+            // find the first batch starting after the token; the one before it holds the token
+            let mut low = 0;
+            let mut high = *self.total_batches;
+            while low < high {
+                let mid = low + (high - low) / 2;
+                if *self.batch_starts.get(&mid).unwrap_or(&0) <= token_id {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+            if low == 0 {
+                return None;
+            }
+            self.batch_starts.get(&(low - 1)).cloned()
+        }
+
+        fn is_token_owner(&self, of: &AccountId, token_id: u64) -> bool {
+            let owner = self.owner_of_impl(token_id);
+            if let None = owner {
+                return false;
+            }
+            let owner = owner.unwrap();
+            if owner != *of {
+                return false;
+            }
+            true
+        }
+
+        /// Transfers an operator may still make for an owner, if it passes the operator filter
+        fn remaining_allowance_impl(&self, owner: &AccountId, operator: &AccountId) -> u32 {
+            if !self.is_allowed_operator(operator) {
+                return 0;
+            }
+            *self.operator_allowances.get(&(*owner, *operator)).unwrap_or(&0)
+        }
+
+        /// Whether an operator passes the operator filter
+        fn is_allowed_operator(&self, operator: &AccountId) -> bool {
+            !*self.operator_filter || *self.registered_operators.get(operator).unwrap_or(&false)
+        }
+
+        /// Whether an account may move a token on behalf of its owner
+        fn is_authorized(&self, spender: &AccountId, owner: &AccountId, token_id: u64) -> bool {
+            if spender == owner {
+                return true;
+            }
+            // an operator removed from the registry loses the approvals it was given
+            if !self.is_allowed_operator(spender) {
+                return false;
+            }
+            if self.approvals.get(&token_id) == Some(spender) {
+                return true;
+            }
+            let in_range = self.range_approvals.get(owner)
+                .map(|ranges| ranges.iter().any(|(s, from_id, to_id)| s == spender && *from_id <= token_id && token_id <= *to_id))
+                .unwrap_or(false);
+            in_range || *self.operator_approvals.get(&(*owner, *spender)).unwrap_or(&false)
+        }
+
+        /// Approves a spender for a token, recording it in the owner's approval index
+        fn set_approval_impl(&mut self, owner: AccountId, token_id: u64, spender: AccountId) {
+            self.approvals.insert(token_id, spender);
+
+            let mut token_ids = self.owner_approvals.get(&owner).cloned().unwrap_or_default();
+            if !token_ids.contains(&token_id) {
+                token_ids.push(token_id);
+                self.owner_approvals.insert(owner, token_ids);
+            }
+        }
+
+        /// Removes the approval of a token, if any, and drops it from the owner's approval index
+        fn clear_approval(&mut self, owner: AccountId, token_id: u64) {
+            if self.approvals.remove(&token_id).is_none() {
+                return;
+            }
+
+            let mut token_ids = self.owner_approvals.get(&owner).cloned().unwrap_or_default();
+            token_ids.retain(|id| *id != token_id);
+            self.owner_approvals.insert(owner, token_ids);
+        }
+
+        /// Whether every token of a bundle is held by the given account
+        fn is_bundle_owner(&self, of: &AccountId, bundle_id: u64) -> bool {
+            let token_ids = self.bundles.get(&bundle_id);
+            if let None = token_ids {
+                return false;
+            }
+            token_ids.unwrap().iter().all(|token_id| self.is_token_owner(of, *token_id))
+        }
+
+        /// Whether a token is still time-locked at the given block
+        fn is_locked(&self, token_id: u64, block: BlockNumber) -> bool {
+            let unlock_at = self.batch_start_of(token_id)
+                .and_then(|start_id| self.unlock_blocks.get(&start_id).cloned())
+                .unwrap_or(0);
+            block < unlock_at
+        }
+
+        /// Whether minting more tokens outside the reserve keeps within the max supply
+        fn fits_public_supply(&self, value: TokenCount) -> bool {
+            if *self.max_supply == 0 {
+                return true;
+            }
+            let available = *self.max_supply - *self.total_minted - *self.reserved_remaining;
+            value <= available
+        }
+
+        /// Whether an account holds the minting role; the owner does unless a minter is set
+        fn is_minter(&self, account: &AccountId) -> bool {
+            match *self.minter {
+                Some(minter) => *account == minter,
+                None => *account == *self.owner,
+            }
+        }
+
+        /// Whether a leaf of the current claim root has been claimed
+        fn is_claimed_impl(&self, index: u64) -> bool {
+            let word = *self.claimed.get(&(*self.claim_round, index / 64)).unwrap_or(&0);
+            word & (1 << (index % 64)) != 0
+        }
+
+        /// Whether an asset URI is an IPFS CID or uses one of the allowed schemes
+        fn is_valid_uri(&self, uri: &[u8]) -> bool {
+            is_ipfs_uri(uri) || self.allowed_uri_schemes.iter().any(|scheme| has_scheme(uri, scheme))
+        }
+
+        /// Whether a max supply leaves room for the tokens already minted and the reserve;
+        /// a reserve is only held back from a capped supply
+        fn is_valid_reserve(&self, max_supply: TokenCount, reserved: TokenCount) -> bool {
+            if max_supply == 0 {
+                return reserved == 0;
+            }
+            max_supply >= *self.total_minted && max_supply - *self.total_minted >= reserved
+        }
+
+        /// Applies the same checks to an admin action as its setter does to its arguments
+        fn check_action(&self, action: &Action) -> Result<(), Error> {
+            let valid = match action {
+                Action::SetReserve(max_supply, reserved) => self.is_valid_reserve(*max_supply, *reserved),
+                Action::SetAllowedUriSchemes(schemes) => is_valid_schemes(schemes),
+                Action::SetGovernance(_, quorum_bps) => is_valid_quorum(*quorum_bps),
+                Action::SetSigners(signers, threshold) => is_valid_signers(signers, *threshold),
+                _ => true,
+            };
+            if !valid {
+                return Err(Error::InvalidInput);
+            }
+            Ok(())
+        }
+
+        /// Carries out an admin action once it has waited out the timelock
+        fn apply_action(&mut self, action: Action) -> Result<(), Error> {
+            // the state may have moved on since the action was scheduled
+            self.check_action(&action)?;
+
+            match action {
+                Action::SetRenewalPeriod(period) => {
+                    self.renewal_period.set(period);
+                }
+                Action::SetFusionCooldown(cooldown) => {
+                    self.fusion_cooldown.set(cooldown);
+                }
+                Action::TransferOwnership(new_owner) => {
+                    self.owner.set(new_owner);
+                }
+                Action::SetTimelockDelay(delay) => {
+                    self.timelock_delay.set(delay);
+                }
+                Action::SetMinter(minter) => {
+                    self.minter.set(minter);
+                }
+                Action::SetBridge(bridge) => {
+                    self.bridge.set(bridge);
+                }
+                Action::SetHistoryLength(length) => {
+                    self.history_length.set(length);
+                }
+                Action::SetPaused(paused) => {
+                    self.paused.set(paused);
+                }
+                Action::SetReserve(max_supply, reserved) => {
+                    self.max_supply.set(max_supply);
+                    self.reserved_remaining.set(reserved);
+                }
+                Action::SetMaxPerHolder(max_per_holder) => {
+                    self.max_per_holder.set(max_per_holder);
+                }
+                Action::SetTransferCooldown(cooldown) => {
+                    self.transfer_cooldown.set(cooldown);
+                }
+                Action::SetOperatorFilter(enabled) => {
+                    self.operator_filter.set(enabled);
+                }
+                Action::RegisterOperator(operator, registered) => {
+                    if registered == true {
+                        self.registered_operators.insert(operator, true);
+                    } else {
+                        self.registered_operators.remove(&operator);
+                    }
+                }
+                Action::SetOracle(oracle, interval) => {
+                    self.oracle.set(oracle);
+                    self.oracle_interval.set(interval);
+                }
+                Action::SetAllowedUriSchemes(schemes) => {
+                    self.allowed_uri_schemes.set(schemes);
+                }
+                Action::SetGovernance(voting_period, quorum_bps) => {
+                    self.voting_period.set(voting_period);
+                    self.quorum_bps.set(quorum_bps);
+                }
+                Action::SetSigners(signers, threshold) => {
+                    self.signers.set(signers);
+                    self.signer_threshold.set(threshold);
+                }
+                Action::SetSuperAdmin(super_admin) => {
+                    self.super_admin.set(super_admin);
+                }
+            }
+            Ok(())
+        }
+
+        /// Queues an admin action behind the timelock, returning its schedule_id and the
+        /// first block it can be carried out in
+        fn schedule_impl(&mut self, action: Action, block: BlockNumber, cancellable: bool) -> Result<(u64, BlockNumber), Error> {
+            self.check_action(&action)?;
+            let eta = block.checked_add(*self.timelock_delay).ok_or(Error::Overflow)?;
+            let schedule_id = self.total_scheduled.checked_add(1).ok_or(Error::Overflow)?;
+
+            self.scheduled.insert(schedule_id, ScheduledAction { action: action, eta: eta, cancellable: cancellable });
+            self.total_scheduled.set(schedule_id);
+            Ok((schedule_id, eta))
+        }
+
+        /// Confirmations of an admin request from the current signers; confirmations
+        /// from removed signers no longer count
+        fn confirmations_impl(&self, request_id: u64) -> u32 {
+            self.signers
+                .iter()
+                .filter(|signer| self.admin_confirmations.get(&(request_id, **signer)).is_some())
+                .count() as u32
+        }
+
+        /// Schedules the action of an admin request if it has reached the threshold, returning
+        /// the schedule_id and eta if it did
+        fn schedule_admin_request(&mut self, request_id: u64, block: BlockNumber) -> Result<Option<(u64, BlockNumber)>, Error> {
+            if self.confirmations_impl(request_id) < *self.signer_threshold {
+                return Ok(None);
+            }
+
+            let request = self.admin_requests.get(&request_id).cloned();
+            if let None = request {
+                return Ok(None);
+            }
+            let mut request = request.unwrap();
+            if request.executed == true {
+                return Ok(None);
+            }
+
+            let scheduled = self.schedule_impl(request.action.clone(), block, false)?;
+            request.executed = true;
+            self.admin_requests.insert(request_id, request);
+            Ok(Some(scheduled))
+        }
+
+        /// First block a token can be transferred in, counting the cooldown from its mint
+        fn transferable_at_impl(&self, token_id: u64) -> BlockNumber {
+            if *self.transfer_cooldown == 0 {
+                return 0;
+            }
+            self.batch_start_of(token_id)
+                .and_then(|start_id| self.mint_origins.get(&start_id))
+                .map(|(_, minted_at)| minted_at.saturating_add(*self.transfer_cooldown))
+                .unwrap_or(0)
+        }
+
+        /// Whether a token is held in bridge custody or gift escrow
+        fn is_in_custody(&self, token_id: u64) -> bool {
+            self.bridge_locks.get(&token_id).is_some() || self.gift_hashes.get(&token_id).is_some()
+        }
+
+        /// Whether a token sits anywhere below the given ancestor
+        fn is_descendant_of(&self, token_id: u64, ancestor_id: u64) -> bool {
+            let mut current = token_id;
+            while let Some(parent_id) = self.parents.get(&current) {
+                if *parent_id == ancestor_id {
+                    return true;
+                }
+                current = *parent_id;
+            }
+            false
+        }
+
+        /// Transfers token from a specified address to another address, returning the ids
+        /// that moved: the token and everything nested below it.
+        fn transfer_impl(&mut self, from: AccountId, to: AccountId, token_id: u64, block: BlockNumber) -> Result<Vec<u64>, Error> {
+            if !self.is_token_owner(&from, token_id) {
+                return Err(Error::NotOwner);
+            }
+
+            // sending to the zero account would silently destroy the token
+            if is_zero_account(&to) {
+                return Err(Error::ZeroAccount);
+            }
+
+            // sending to the current holder would only churn storage and emit a misleading event
+            if from == to {
+                return Err(Error::SelfTransfer);
+            }
+
+            self.before_token_transfer(Some(from), Some(to), token_id, 1, block, None)?;
+            self.move_token(from, to, token_id, block)
+        }
+
+        /// Approves or disapproves an account to send a token on behalf of its owner, the caller
+        fn approval_impl(&mut self, caller: AccountId, to: AccountId, token_id: u64, approved: bool) -> Result<(), Error> {
+            // return if caller is not the token owner
+            let token_owner = self.owner_of_impl(token_id);
+            if let None = token_owner {
+                return Err(Error::TokenNotFound);
+            }
+
+            let token_owner = token_owner.unwrap();
+            if token_owner != caller {
+                return Err(Error::NotOwner);
+            }
+
+            // tokens cannot be approved away to the zero account, nor back to their owner
+            if is_zero_account(&to) {
+                return Err(Error::ZeroAccount);
+            }
+            if to == token_owner {
+                return Err(Error::SelfApproval);
+            }
+            // with the operator filter on only registered operators can be approved
+            if approved == true && !self.is_allowed_operator(&to) {
+                return Err(Error::Unauthorized);
+            }
+
+            let approvals = self.approvals.get(&token_id);
+
+            // insert approval if
+            if let None = approvals {
+                if approved == true {
+                    self.set_approval_impl(token_owner, token_id, to);
+                } else {
+                    return Err(Error::NotFound);
+                }
+
+            } else {
+                let existing = *approvals.unwrap();
+
+                // remove existing owner if disapproving; another account has no approval to revoke
+                if approved == false {
+                    if existing != to {
+                        return Err(Error::NotFound);
+                    }
+                    self.clear_approval(token_owner, token_id);
+                }
+
+                // overwrite or insert if approving is true
+                if approved == true {
+                    self.set_approval_impl(token_owner, token_id, to);
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Approves or disapproves an operator to send any of the caller's tokens
+        fn set_approval_for_all_impl(&mut self, caller: AccountId, operator: AccountId, approved: bool) -> Result<(), Error> {
+            if operator == caller {
+                return Err(Error::SelfApproval);
+            }
+            if is_zero_account(&operator) {
+                return Err(Error::ZeroAccount);
+            }
+
+            // with the filter on, only registered operators can be approved
+            if approved == true && !self.is_allowed_operator(&operator) {
+                return Err(Error::Unauthorized);
+            }
+
+            if approved == true {
+                self.operator_approvals.insert((caller, operator), true);
+            } else {
+                self.operator_approvals.remove(&(caller, operator));
+            }
+            Ok(())
+        }
+
+        /// Runs before tokens are minted (from is None), transferred, or burned (to is None).
+        /// Every rule that can hold tokens in place or keep them from an account lives here,
+        /// so mints, transfers and burns apply it alike. bundle_id names the bundle being
+        /// moved, if any, whose tokens may then move together
+        fn before_token_transfer(&self, from: Option<AccountId>, to: Option<AccountId>, first_id: u64, count: TokenCount, block: BlockNumber, bundle_id: Option<u64>) -> Result<(), Error> {
+            if *self.paused == true {
+                return Err(Error::Paused);
+            }
+            self.check_token_transfer(from, to, first_id, count, block, bundle_id, false)
+        }
+
+        /// The rules of before_token_transfer other than the pause. A forced transfer, made by the
+        /// super admin, skips the time locks and cooldowns its owner could otherwise hide behind
+        fn check_token_transfer(&self, from: Option<AccountId>, to: Option<AccountId>, first_id: u64, count: TokenCount, block: BlockNumber, bundle_id: Option<u64>, forced: bool) -> Result<(), Error> {
+            // new tokens carry none of the state that holds existing tokens in place
+            let mut incoming = count;
+            if from.is_some() {
+                incoming = 0;
+                for token_id in first_id..first_id.saturating_add(count) {
+                    // bundled tokens can only move together with their bundle
+                    if let Some(token_bundle) = self.token_to_bundle.get(&token_id) {
+                        if Some(*token_bundle) != bundle_id {
+                            return Err(Error::TokenBundled);
+                        }
+                    }
+                    if forced == false && self.is_locked(token_id, block) {
+                        return Err(Error::TokenLocked);
+                    }
+                    if self.is_in_custody(token_id) {
+                        return Err(Error::TokenInCustody);
+                    }
+                    if forced == false && to.is_some() && block < self.transferable_at_impl(token_id) {
+                        return Err(Error::TransferCooldown);
+                    }
+                    // nested tokens only move with their parent, and burning a parent would orphan its children
+                    if self.parents.get(&token_id).is_some() || (to.is_none() && self.has_children(token_id)) {
+                        return Err(Error::TokenNested);
+                    }
+                    // nested children arrive together with the token
+                    incoming = incoming.saturating_add(self.family_size(token_id));
+                }
+            }
+
+            if let Some(to) = to {
+                if !self.fits_holder_cap(&to, incoming) {
+                    return Err(Error::HolderCapExceeded);
+                }
+            }
+            Ok(())
+        }
+
+        /// Runs after tokens are minted (from is None), moved, or burned (to is None)
+        fn after_token_transfer(&mut self, from: Option<AccountId>, to: Option<AccountId>, first_id: u64, count: TokenCount, block: BlockNumber) {
+            if let (Some(from), Some(to)) = (from, to) {
+                for token_id in first_id..first_id.saturating_add(count) {
+                    self.record_transfer(from, to, token_id, block);
+                    let transfers = self.transfer_counts.get(&token_id).unwrap_or(&0).saturating_add(1);
+                    self.transfer_counts.insert(token_id, transfers);
+                }
+            }
+        }
+
+        /// Whether any token is nested inside the given token
+        fn has_children(&self, token_id: u64) -> bool {
+            self.children.get(&token_id).map(|children| !children.is_empty()).unwrap_or(false)
+        }
+
+        /// Number of tokens that move with a token: itself and everything nested below it
+        fn family_size(&self, token_id: u64) -> TokenCount {
+            let children = self.children.get(&token_id).cloned().unwrap_or_default();
+            children.iter().fold(1, |total: TokenCount, child_id| total.saturating_add(self.family_size(*child_id)))
+        }
+
+        /// Whether an account can receive more tokens without going over the holder cap
+        fn fits_holder_cap(&self, account: &AccountId, incoming: TokenCount) -> bool {
+            if *self.max_per_holder == 0 {
+                return true;
+            }
+            let held = *self.owner_to_token_count.get(account).unwrap_or(&0);
+            held.saturating_add(incoming) <= *self.max_per_holder
+        }
+
+        /// Destroys a token held by the given account, clearing everything attached to it
+        fn burn_impl(&mut self, from: AccountId, token_id: u64, block: BlockNumber) -> Result<(), Error> {
+            if !self.is_token_owner(&from, token_id) {
+                return Err(Error::NotOwner);
+            }
+            self.before_token_transfer(Some(from), None, token_id, 1, block, None)?;
+
+            let from_owner_count = self.owner_to_token_count.get(&from).unwrap_or(&0)
+                .checked_sub(1)
+                .ok_or(Error::Underflow)?;
+            let total_burned = self.total_burned.checked_add(1).ok_or(Error::Overflow)?;
+
+            self.id_to_owner.remove(&token_id);
+            self.burned.insert(token_id, true);
+            self.clear_approval(from, token_id);
+            self.users.remove(&token_id);
+            self.expirations.remove(&token_id);
+            self.assets.remove(&token_id);
+            self.pending_assets.remove(&token_id);
+            self.genes.remove(&token_id);
+            self.fusion_ready_at.remove(&token_id);
+            self.clear_handle(token_id);
+
+            self.remove_from_enumeration(from, token_id, from_owner_count);
+            self.set_balance(from, from_owner_count)?;
+            self.checkpoint_supply();
+            self.total_burned.set(total_burned);
+            self.after_token_transfer(Some(from), None, token_id, 1, block);
+            Ok(())
+        }
+
+        /// Brings a burned token back into circulation under the given owner, as a mint would
+        fn restore_impl(&mut self, owner: AccountId, token_id: u64, block: BlockNumber) -> Result<(), Error> {
+            if is_zero_account(&owner) {
+                return Err(Error::ZeroAccount);
+            }
+            self.before_token_transfer(None, Some(owner), token_id, 1, block, None)?;
+
+            let owner_count = self.owner_to_token_count.get(&owner).unwrap_or(&0)
+                .checked_add(1)
+                .ok_or(Error::Overflow)?;
+            let total_burned = self.total_burned.checked_sub(1).ok_or(Error::Underflow)?;
+
+            self.burned.remove(&token_id);
+            self.id_to_owner.insert(token_id, owner);
+
+            self.add_to_enumeration(owner, token_id, owner_count - 1);
+            self.set_balance(owner, owner_count)?;
+            self.checkpoint_supply();
+            self.total_burned.set(total_burned);
+            Ok(())
+        }
+
+        /// Releases the handle of a token, if it has one
+        fn clear_handle(&mut self, token_id: u64) {
+            if let Some(handle) = self.token_handles.remove(&token_id) {
+                self.handles.remove(&handle);
+            }
+        }
+
+        /// Moves every token of a holder to another account, returning the ids that moved.
+        /// Each token passes the transfer hook: tokens it holds in place, such as nested
+        /// tokens or those in bridge custody, stay put, and so does any bundle with one of
+        /// them, while a pause or a full recipient refuses the whole sweep
+        fn sweep_tokens(&mut self, from: AccountId, to: AccountId, block: BlockNumber) -> Result<Vec<u64>, Error> {
+            let count = *self.owner_to_token_count.get(&from).unwrap_or(&0);
+            let mut token_ids = Vec::new();
+            for index in 0..count {
+                if let Some(token_id) = self.token_at_index(&from, index) {
+                    token_ids.push(token_id);
+                }
+            }
+
+            // every check runs before the first token moves
+            let mut movable = Vec::new();
+            let mut held_bundles = Vec::new();
+            for token_id in token_ids {
+                // all of a bundle's tokens belong to the holder, so its tokens are swept together
+                let bundle_id = self.token_to_bundle.get(&token_id).cloned();
+                match self.before_token_transfer(Some(from), Some(to), token_id, 1, block, bundle_id) {
+                    Ok(()) => movable.push((token_id, bundle_id)),
+                    Err(error @ Error::Paused) | Err(error @ Error::HolderCapExceeded) => return Err(error),
+                    Err(_) => {
+                        if let Some(bundle_id) = bundle_id {
+                            held_bundles.push(bundle_id);
+                        }
+                    }
+                }
+            }
+            movable.retain(|(_, bundle_id)| bundle_id.map(|bundle_id| !held_bundles.contains(&bundle_id)).unwrap_or(true));
+
+            let incoming = movable.iter().fold(0, |total: TokenCount, (token_id, _)| total.saturating_add(self.family_size(*token_id)));
+            if !self.fits_holder_cap(&to, incoming) {
+                return Err(Error::HolderCapExceeded);
+            }
+
+            let mut moved = Vec::new();
+            for (token_id, _) in movable {
+                moved.extend(self.move_token(from, to, token_id, block)?);
+            }
+            Ok(moved)
+        }
+
+        /// Appends a transfer to a token's history, dropping the oldest beyond the history length
+        fn record_transfer(&mut self, from: AccountId, to: AccountId, token_id: u64, block: BlockNumber) {
+            let length = *self.history_length as usize;
+            if length == 0 {
+                return;
+            }
+
+            let mut history = self.transfer_history.get(&token_id).cloned().unwrap_or_default();
+            history.push((from, to, block));
+            if history.len() > length {
+                let excess = history.len() - length;
+                history.drain(..excess);
+            }
+            self.transfer_history.insert(token_id, history);
+        }
+
+        /// Transfers all tokens of a bundle, only once every one of them has passed the transfer hook,
+        /// returning the ids that moved
+        fn transfer_bundle_impl(&mut self, from: AccountId, to: AccountId, bundle_id: u64, block: BlockNumber) -> Result<Vec<u64>, Error> {
+            if !self.is_bundle_owner(&from, bundle_id) {
+                return Err(Error::NotOwner);
+            }
+            if is_zero_account(&to) {
+                return Err(Error::ZeroAccount);
+            }
+            if from == to {
+                return Err(Error::SelfTransfer);
+            }
+
+            // the hook sees one token at a time, so the holder cap is checked for the bundle as a whole
+            let token_ids = self.bundles.get(&bundle_id).cloned().unwrap_or_default();
+            let mut incoming: TokenCount = 0;
+            for token_id in token_ids.iter() {
+                self.before_token_transfer(Some(from), Some(to), *token_id, 1, block, Some(bundle_id))?;
+                incoming = incoming.saturating_add(self.family_size(*token_id));
+            }
+            if !self.fits_holder_cap(&to, incoming) {
+                return Err(Error::HolderCapExceeded);
+            }
+            let mut moved = Vec::new();
+            for token_id in token_ids {
+                moved.extend(self.move_token(from, to, token_id, block)?);
+            }
+            Ok(moved)
+        }
+
+        /// Moves a token between accounts, updating owner token counts, and returns the ids
+        /// that moved, the token first and then everything nested below it, for the caller
+        /// to emit an EventTransfer for each
+        fn move_token(&mut self, from: AccountId, to: AccountId, token_id: u64, block: BlockNumber) -> Result<Vec<u64>, Error> {
+            //update owner token counts, checked before anything is written
+            let from_owner_count = self.owner_to_token_count.get(&from).unwrap_or(&0)
+                .checked_sub(1)
+                .ok_or(Error::Underflow)?;
+            let to_owner_count = self.owner_to_token_count.get(&to).unwrap_or(&0)
+                .checked_add(1)
+                .ok_or(Error::Overflow)?;
+
+            self.id_to_owner.insert(token_id, to);
+
+            // neither an approval nor a user role survives a change of owner
+            self.clear_approval(from, token_id);
+            self.users.remove(&token_id);
+
+            self.remove_from_enumeration(from, token_id, from_owner_count);
+            self.add_to_enumeration(to, token_id, to_owner_count - 1);
+            self.set_balance(from, from_owner_count)?;
+            self.set_balance(to, to_owner_count)?;
+            self.after_token_transfer(Some(from), Some(to), token_id, 1, block);
+
+            // nested tokens follow their parent
+            let mut moved = Vec::new();
+            moved.push(token_id);
+            let children = self.children.get(&token_id).cloned().unwrap_or_default();
+            for child_id in children {
+                moved.extend(self.move_token(from, to, child_id, block)?);
+            }
+            Ok(moved)
+        }
+
+        /// Return the balance an owner had at a snapshot, 0 for unknown snapshots
+        fn balance_at_impl(&self, owner: &AccountId, snapshot_id: u64) -> TokenCount {
+            if snapshot_id == 0 || snapshot_id > *self.current_snapshot {
+                return 0;
+            }
+            let count = *self.balance_snapshot_count.get(owner).unwrap_or(&0);
+            let current = *self.owner_to_token_count.get(owner).unwrap_or(&0);
+            snapshot_value(u64::from(count), |i| self.balance_snapshots.get(&(*owner, i as u32)).cloned(), snapshot_id, current)
+        }
+
+        /// Return the circulating supply at a snapshot, 0 for unknown snapshots
+        fn supply_at_impl(&self, snapshot_id: u64) -> TokenCount {
+            if snapshot_id == 0 || snapshot_id > *self.current_snapshot {
+                return 0;
+            }
+            let current = *self.total_minted - *self.total_burned;
+            snapshot_value(*self.total_supply_snapshots, |i| self.supply_snapshots.get(&i).cloned(), snapshot_id, current)
+        }
+
+        /// Return the votes delegated to an account at a snapshot, 0 for unknown snapshots
+        fn votes_at_impl(&self, account: &AccountId, snapshot_id: u64) -> TokenCount {
+            if snapshot_id == 0 || snapshot_id > *self.current_snapshot {
+                return 0;
+            }
+            let count = *self.voting_power_snapshot_count.get(account).unwrap_or(&0);
+            let current = *self.voting_power.get(account).unwrap_or(&0);
+            snapshot_value(u64::from(count), |i| self.voting_power_snapshots.get(&(*account, i as u32)).cloned(), snapshot_id, current)
+        }
+
+        /// Return the account that votes with a holder's tokens
+        fn delegate_of_impl(&self, holder: &AccountId) -> AccountId {
+            *self.delegates.get(holder).unwrap_or(holder)
+        }
+
+        /// Moves votes from one delegate to another
+        fn move_votes(&mut self, from: AccountId, to: AccountId, votes: TokenCount) -> Result<(), Error> {
+            if from == to || votes == 0 {
+                return Ok(());
+            }
+            let from_votes = *self.voting_power.get(&from).unwrap_or(&0);
+            let to_votes = *self.voting_power.get(&to).unwrap_or(&0);
+            self.set_voting_power(from, from_votes.saturating_sub(votes))?;
+            self.set_voting_power(to, to_votes.saturating_add(votes))
+        }
+
+        /// Writes the votes delegated to an account, first checkpointing the old votes for the
+        /// current snapshot
+        fn set_voting_power(&mut self, account: AccountId, votes: TokenCount) -> Result<(), Error> {
+            let snapshot_id = *self.current_snapshot;
+            if snapshot_id > 0 {
+                let previous = *self.voting_power.get(&account).unwrap_or(&0);
+                push_checkpoint(&mut self.voting_power_snapshots, &mut self.voting_power_snapshot_count, account, snapshot_id, previous)?;
+            }
+            self.voting_power.insert(account, votes);
+            Ok(())
+        }
+
+        /// Writes an owner's token count, first checkpointing the old count for the current snapshot
+        /// and moving the difference in votes to or from the owner's delegate
+        fn set_balance(&mut self, owner: AccountId, count: TokenCount) -> Result<(), Error> {
+            // holders are counted as they gain their first token and lose their last
+            let previous = *self.owner_to_token_count.get(&owner).unwrap_or(&0);
+            let holder_count = if previous == 0 && count > 0 {
+                self.holder_count.checked_add(1).ok_or(Error::Overflow)?
+            } else if previous > 0 && count == 0 {
+                self.holder_count.checked_sub(1).ok_or(Error::Underflow)?
+            } else {
+                *self.holder_count
+            };
+
+            let snapshot_id = *self.current_snapshot;
+            if snapshot_id > 0 {
+                push_checkpoint(&mut self.balance_snapshots, &mut self.balance_snapshot_count, owner, snapshot_id, previous)?;
+            }
+
+            let delegate = self.delegate_of_impl(&owner);
+            let votes = *self.voting_power.get(&delegate).unwrap_or(&0);
+            if count > previous {
+                self.set_voting_power(delegate, votes.saturating_add(count - previous))?;
+            } else {
+                self.set_voting_power(delegate, votes.saturating_sub(previous - count))?;
+            }
+
+            self.holder_count.set(holder_count);
+            self.owner_to_token_count.insert(owner, count);
+            Ok(())
+        }
+
+        /// Checkpoints the circulating supply for the current snapshot before it changes
+        fn checkpoint_supply(&mut self) {
+            let snapshot_id = *self.current_snapshot;
+            if snapshot_id == 0 {
+                return;
+            }
+            // only the last checkpoint is read, however many snapshots were taken
+            let count = *self.total_supply_snapshots;
+            let checkpointed = count > 0 && self.supply_snapshots.get(&(count - 1)).map(|(id, _)| *id >= snapshot_id).unwrap_or(false);
+            if checkpointed == false {
+                let supply = *self.total_minted - *self.total_burned;
+                self.supply_snapshots.insert(count, (snapshot_id, supply));
+                self.total_supply_snapshots.set(count + 1);
+            }
+        }
+
+        /// Places a token at the given index of an owner's enumeration
+        fn add_to_enumeration(&mut self, owner: AccountId, token_id: u64, index: TokenCount) {
+            self.owned_tokens.insert((owner, index), token_id);
+            self.owned_index.insert(token_id, index);
+        }
+
+        /// Removes a token from an owner's enumeration by moving the owner's last token,
+        /// at last_index, into its place
+        fn remove_from_enumeration(&mut self, owner: AccountId, token_id: u64, last_index: TokenCount) {
+            let index = self.index_of_token(token_id).unwrap_or(last_index);
+            if index != last_index {
+                let last_token_id = self.token_at_index(&owner, last_index).unwrap_or(token_id);
+                self.owned_tokens.insert((owner, index), last_token_id);
+                self.owned_index.insert(last_token_id, index);
+            }
+            self.owned_tokens.remove(&(owner, last_index));
+            self.owned_index.remove(&token_id);
+        }
+
+        /// Lays a mint batch out in an owner's enumeration from the given index on, which is the
+        /// owner's token count before the mint. Segments starting at or past that index only
+        /// covered tokens the owner has since given up, so they are dropped
+        fn push_segment(&mut self, owner: AccountId, first_id: u64, index: TokenCount) {
+            let mut count = *self.owned_segment_count.get(&owner).unwrap_or(&0);
+            while count > 0 && self.owned_segments.get(&(owner, count - 1)).map(|(_, start)| *start >= index).unwrap_or(false) {
+                count -= 1;
+            }
+            self.owned_segments.insert((owner, count), (first_id, index));
+            self.owned_segment_count.insert(owner, count + 1);
+        }
+
+        /// Return the token at an index of an owner's enumeration, from an entry of its own
+        /// or else from the segment laid out over that index
+        fn token_at_index(&self, owner: &AccountId, index: TokenCount) -> Option<u64> {
+            if index >= *self.owner_to_token_count.get(owner).unwrap_or(&0) {
+                return None;
+            }
+            if let Some(token_id) = self.owned_tokens.get(&(*owner, index)) {
+                return Some(*token_id);
+            }
+
+            // segments start at increasing indexes, so the last one starting at or before
+            // the index covers it
+            let mut low = 0;
+            let mut high = *self.owned_segment_count.get(owner).unwrap_or(&0);
+            while low < high {
+                let mid = low + (high - low) / 2;
+                if self.owned_segments.get(&(*owner, mid)).map(|(_, start)| *start <= index).unwrap_or(false) {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+            if low == 0 {
+                return None;
+            }
+            self.owned_segments.get(&(*owner, low - 1)).map(|(first_id, start)| first_id + (index - start))
+        }
+
+        /// Return a token's index in its owner's enumeration, from an entry of its own or
+        /// else from where its mint batch was laid out
+        fn index_of_token(&self, token_id: u64) -> Option<TokenCount> {
+            if let Some(index) = self.owned_index.get(&token_id) {
+                return Some(*index);
+            }
+            self.batch_start_of(token_id)
+                .and_then(|start_id| self.mint_batches.get(&start_id).map(|(_, _, index)| index + (token_id - start_id)))
+        }
+
+        /// minting of new tokens implementation, constant cost regardless of value: ownership
+        /// and the enumeration are both recorded once per batch.
+        /// Mints ids total_minted + 1 ..= total_minted + value and returns that range
+        fn mint_impl(&mut self, minter: AccountId, receiver: AccountId, value: TokenCount, block: BlockNumber) -> Result<(u64, u64), Error> {
+            if value == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if is_zero_account(&receiver) {
+                return Err(Error::ZeroAccount);
+            }
+
+            let last_id = self.total_minted.checked_add(value).ok_or(Error::Overflow)?;
+            let first_id = *self.total_minted + 1;
+            let receiver_count = self.owner_to_token_count.get(&receiver).unwrap_or(&0)
+                .checked_add(value)
+                .ok_or(Error::Overflow)?;
+            self.before_token_transfer(None, Some(receiver), first_id, value, block, None)?;
+
+            // record the whole range once; its tokens resolve to it until they change hands
+            let batch_index = *self.total_batches;
+            let first_index = receiver_count - value;
+            self.mint_batches.insert(first_id, (last_id, receiver, first_index));
+            self.batch_starts.insert(batch_index, first_id);
+            self.total_batches.set(batch_index + 1);
+            self.mint_origins.insert(first_id, (minter, block));
+
+            self.push_segment(receiver, first_id, first_index);
+
+            // update total supply of receiver
+            self.set_balance(receiver, receiver_count)?;
+
+            // update total supply
+            self.checkpoint_supply();
+            self.total_minted.set(last_id);
+            self.after_token_transfer(None, Some(receiver), first_id, value, block);
+            Ok((first_id, last_id))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "test-env"))]
+mod tests {
+    use super::*;
+    use crate::testing::{account, alice, as_caller, bob, charlie, dave, eve, set_block_number, set_caller, Fixture};
+    use proptest::prelude::*;
+
+    /// Deploys the contract in the state a testing::Fixture describes, leaving alice as the caller
+    macro_rules! deploy {
+        ($fixture:expr) => {{
+            let fixture = $fixture;
+            set_caller(alice());
+            let mut contract = NFToken::deploy_mock(fixture.tokens);
+            for token_id in 1..=fixture.tokens {
+                let holder = fixture.holder_of(token_id);
+                if holder != alice() {
+                    contract.transfer(holder, token_id).unwrap();
+                }
+            }
+            for (token_id, spender) in fixture.approvals.iter() {
+                set_caller(fixture.holder_of(*token_id));
+                contract.approval(*spender, *token_id, true).unwrap();
+            }
+            set_caller(alice());
+            contract
+        }};
+    }
+
+    #[test]
+    fn it_works() {
+
+        // deploying and minting initial tokens
+        let mut _nftoken = deploy!(Fixture::new(100));
+
+        let total_minted = _nftoken.total_minted();
+        assert_eq!(total_minted, 100);
+
+        // transferring token_id from alice to bob
+        _nftoken.transfer(bob(), 1).unwrap();
+
+        let alice_balance = _nftoken.balance_of(alice());
+        let mut bob_balance = _nftoken.balance_of(bob());
+
+        assert_eq!(alice_balance, 99);
+        assert_eq!(bob_balance, 1);
+
+        // approve charlie to send token_id 2 from alice's account
+        _nftoken.approval(charlie(), 2, true).unwrap();
+        assert_eq!(_nftoken.is_approved(2, charlie()), true);
+
+        // overwrite charlie's approval with dave's approval
+        _nftoken.approval(dave(), 2, true).unwrap();
+        assert_eq!(_nftoken.is_approved(2, dave()), true);
+
+        // remove dave from approvals
+        _nftoken.approval(dave(), 2, false).unwrap();
+        assert_eq!(_nftoken.is_approved(2, dave()), false);
+
+        // transfer_from function: caller is token owner
+        _nftoken.approval(charlie(), 3, true).unwrap();
+        assert_eq!(_nftoken.is_approved(3, charlie()), true);
+
+        _nftoken.transfer_from(bob(), 3).unwrap();
+        bob_balance = _nftoken.balance_of(bob());
+
+        assert_eq!(bob_balance, 2);
+    }
+
+    #[test]
+    fn operators_are_approved_and_revoked() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // make charlie an operator of alice, then revoke it again
+        assert_eq!(_nftoken.set_approval_for_all(charlie(), true), Ok(()));
+        assert_eq!(_nftoken.is_approved_for_all(alice(), charlie()), true);
+        assert_eq!(_nftoken.set_approval_for_all(charlie(), false), Ok(()));
+        assert_eq!(_nftoken.is_approved_for_all(alice(), charlie()), false);
+    }
+
+    #[test]
+    fn bundles_move_as_one_unit() {
+        let mut _nftoken = deploy!(Fixture::new(4));
+
+        // bundle tokens 1, 2 and 3 and move them to charlie as one unit
+        assert_eq!(_nftoken.create_bundle(vec![1, 2, 3]), Ok(()));
+        assert_eq!(_nftoken.bundle_of(2), 1);
+
+        // bundled tokens cannot be transferred on their own
+        assert!(_nftoken.transfer(bob(), 2).is_err());
+        // a token cannot join two bundles
+        assert!(_nftoken.create_bundle(vec![3, 4]).is_err());
+
+        assert_eq!(_nftoken.transfer_bundle(charlie(), 1), Ok(()));
+        assert_eq!(_nftoken.balance_of(charlie()), 3);
+        assert_eq!(_nftoken.tokens_in_bundle(1), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn lent_tokens_lose_their_user_on_transfer() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // lend token 1 to dave until block 50, then clear it with a transfer
+        assert_eq!(_nftoken.set_user(1, dave(), 50), Ok(()));
+        assert_eq!(_nftoken.user_of(1), Some(dave()));
+        assert_eq!(_nftoken.user_expires(1), 50);
+        _nftoken.transfer(bob(), 1).unwrap();
+        assert_eq!(_nftoken.user_of(1), None);
+    }
+
+    #[test]
+    fn subscription_tokens_are_renewed() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // turn token 1 into a subscription token and renew it
+        assert_eq!(_nftoken.is_valid(1), true);
+        assert_eq!(_nftoken.set_expiry(1, 0), Ok(()));
+        assert_eq!(_nftoken.is_valid(1), false);
+        assert!(_nftoken.renew(1).is_err());
+        _nftoken.set_renewal_period(30).unwrap();
+        assert_eq!(_nftoken.renew(1), Ok(()));
+        assert_eq!(_nftoken.expires_at(1), 30);
+        assert_eq!(_nftoken.is_valid(1), true);
+    }
+
+    #[test]
+    fn locked_mints_cannot_move_or_be_bundled() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // mint tokens locked until block 10; they cannot move or be bundled yet
+        assert_eq!(_nftoken.mint_locked(alice(), 2, 10), Ok((2, 3)));
+        assert_eq!(_nftoken.unlock_block(2), 10);
+        assert!(_nftoken.transfer(bob(), 2).is_err());
+        assert!(_nftoken.create_bundle(vec![2, 3]).is_err());
+    }
+
+    #[test]
+    fn nested_tokens_move_with_their_parent() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+
+        // nest token 2 inside 1 and 3 inside 2, then move the whole tree to dave
+        assert_eq!(_nftoken.nest(2, 1), Ok(()));
+        assert_eq!(_nftoken.nest(3, 2), Ok(()));
+        assert!(_nftoken.nest(1, 3).is_err());
+        assert_eq!(_nftoken.parent_of(3), 2);
+        assert_eq!(_nftoken.children_of(1), vec![2]);
+        assert!(_nftoken.transfer(bob(), 2).is_err());
+
+        assert_eq!(_nftoken.transfer(dave(), 1), Ok(()));
+        assert_eq!(_nftoken.balance_of(dave()), 3);
+
+        // only the owner of the parent can eject its children
+        assert!(_nftoken.eject_child(2, 3).is_err());
+    }
+
+    #[test]
+    fn children_are_equipped_in_slots() {
+        let mut _nftoken = deploy!(Fixture::new(2));
+
+        // equip token 2 in the "weapon" slot of token 1
+        let weapon = b"weapon".to_vec();
+        assert_eq!(_nftoken.nest(2, 1), Ok(()));
+        assert!(_nftoken.equip(1, weapon.clone(), 2).is_err());
+        assert_eq!(_nftoken.set_slot_rule(weapon.clone(), 2, true), Ok(()));
+        assert_eq!(_nftoken.equip(1, weapon.clone(), 2), Ok(()));
+        assert_eq!(_nftoken.equipped_in(1, weapon.clone()), 2);
+        assert!(_nftoken.eject_child(1, 2).is_err());
+        assert_eq!(_nftoken.unequip(1, weapon.clone()), Ok(()));
+        assert_eq!(_nftoken.eject_child(1, 2), Ok(()));
+    }
+
+    #[test]
+    fn assets_are_accepted_in_priority_order() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // propose two assets for token 1 and accept them in priority order
+        assert_eq!(_nftoken.propose_asset(1, b"ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/model.glb".to_vec(), 2), Ok(()));
+        assert_eq!(_nftoken.propose_asset(1, b"ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/image.png".to_vec(), 1), Ok(()));
+        assert_eq!(_nftoken.accept_asset(1, 0), Ok(()));
+        assert_eq!(_nftoken.accept_asset(1, 0), Ok(()));
+        assert!(_nftoken.accept_asset(1, 0).is_err());
+        assert_eq!(
+            _nftoken.assets_of(1),
+            vec![(1, b"ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/image.png".to_vec()), (2, b"ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/model.glb".to_vec())]
+        );
+    }
+
+    #[test]
+    fn fused_tokens_start_their_cooldown() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+
+        // fuse tokens 1 and 2 into a new child that starts its cooldown
+        _nftoken.set_fusion_cooldown(5).unwrap();
+        assert_eq!(_nftoken.fuse(1, 2), Ok(()));
+        assert_eq!(_nftoken.balance_of(alice()), 2);
+        assert!(_nftoken.transfer(bob(), 1).is_err());
+        assert_eq!(_nftoken.fusion_ready_at(4), 5);
+        assert!(_nftoken.fuse(4, 3).is_err());
+
+        // both parents were burned
+        assert_eq!(_nftoken.total_burned(), 2);
+    }
+
+    #[test]
+    fn tokens_are_redeemed_in_campaigns() {
+        let mut _nftoken = deploy!(Fixture::new(4));
+
+        // redeem token 1 in a flagging campaign and token 2 in a burning one
+        assert_eq!(_nftoken.create_campaign(false), Ok(()));
+        assert_eq!(_nftoken.create_campaign(true), Ok(()));
+        assert_eq!(_nftoken.redeem(1, 1), Ok(()));
+        assert!(_nftoken.redeem(1, 1).is_err());
+        assert_eq!(_nftoken.is_redeemed(1, 1), true);
+        let block: BlockNumber = 0;
+        assert_eq!(_nftoken.redemption_of(1, 1), Some(hash_of(&(1u64, 1u64, alice(), block))));
+        assert_eq!(_nftoken.redeem(2, 2), Ok(()));
+        assert!(_nftoken.transfer(bob(), 2).is_err());
+        _nftoken.close_campaign(2).unwrap();
+        assert!(_nftoken.redeem(3, 2).is_err());
+        assert_eq!(_nftoken.total_burned(), 1);
+
+        // ownership inside a batch resolves through explicit entries and burns
+        _nftoken.transfer(bob(), 4).unwrap();
+        assert_eq!(_nftoken.owner_of(1), Some(alice()));
+        assert_eq!(_nftoken.owner_of(2), None);
+        assert_eq!(_nftoken.owner_of(3), Some(alice()));
+        assert_eq!(_nftoken.owner_of(4), Some(bob()));
+        assert_eq!(_nftoken.owner_of(0), None);
+        assert_eq!(_nftoken.owner_of(5), None);
+    }
+
+    #[test]
+    fn handles_are_unique_and_can_be_cleared() {
+        let mut _nftoken = deploy!(Fixture::new(2));
+
+        // give token 1 a handle; handles are unique and can be cleared
+        assert_eq!(_nftoken.set_handle(1, b"genesis-001".to_vec()), Ok(()));
+        assert_eq!(_nftoken.token_id_of(b"genesis-001".to_vec()), 1);
+        assert_eq!(_nftoken.handle_of(1), b"genesis-001".to_vec());
+        assert!(_nftoken.set_handle(2, b"genesis-001".to_vec()).is_err());
+        assert_eq!(_nftoken.set_handle(1, Vec::new()), Ok(()));
+        assert_eq!(_nftoken.token_id_of(b"genesis-001".to_vec()), 0);
+    }
+
+    #[test]
+    fn ownership_is_handed_over() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // hand the contract over to bob; alice loses her admin rights
+        assert_eq!(_nftoken.transfer_ownership(bob()), Ok(()));
+        assert_eq!(_nftoken.owner(), bob());
+        assert!(_nftoken.mint(alice(), 1).is_err());
+        assert!(_nftoken.transfer_ownership(alice()).is_err());
+    }
+
+    #[test]
+    fn mint_returns_exact_id_range() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+
+        // deploying minted ids 1 ..= 3
+        assert_eq!(_nftoken.owner_of(1), Some(alice()));
+        assert_eq!(_nftoken.owner_of(3), Some(alice()));
+        assert_eq!(_nftoken.owner_of(4), None);
+
+        // the next batch starts right after the last minted id
+        assert_eq!(_nftoken.mint(bob(), 2), Ok((4, 5)));
+        assert_eq!(_nftoken.owner_of(3), Some(alice()));
+        assert_eq!(_nftoken.owner_of(4), Some(bob()));
+        assert_eq!(_nftoken.owner_of(5), Some(bob()));
+        assert_eq!(_nftoken.owner_of(6), None);
+
+        // a single token mint returns a one-id range
+        assert_eq!(_nftoken.mint(bob(), 1), Ok((6, 6)));
+
+        // minting nothing mints nothing
+        assert!(_nftoken.mint(bob(), 0).is_err());
+        assert_eq!(_nftoken.total_minted(), 6);
+        assert_eq!(_nftoken.balance_of(alice()), 3);
+        assert_eq!(_nftoken.balance_of(bob()), 3);
+    }
+
+    #[test]
+    fn zero_account_is_rejected() {
+        let zero = account(0x0);
+        let mut _nftoken = deploy!(Fixture::new(2));
+
+        assert!(_nftoken.mint(zero, 1).is_err());
+        assert!(_nftoken.transfer(zero, 1).is_err());
+        assert!(_nftoken.transfer_from(zero, 1).is_err());
+        assert!(_nftoken.approval(zero, 1, true).is_err());
+        assert!(_nftoken.set_approval_for_all(zero, true).is_err());
+
+        assert_eq!(_nftoken.owner_of(1), Some(alice()));
+        assert_eq!(_nftoken.balance_of(zero), 0);
+    }
+
+    #[test]
+    fn self_transfer_and_self_approval_are_rejected() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+
+        assert!(_nftoken.transfer(alice(), 1).is_err());
+        assert!(_nftoken.transfer_from(alice(), 1).is_err());
+        assert!(_nftoken.approval(alice(), 1, true).is_err());
+        assert!(_nftoken.set_approval_for_all(alice(), true).is_err());
+
+        assert_eq!(_nftoken.create_bundle(vec![2, 3]), Ok(()));
+        assert!(_nftoken.transfer_bundle(alice(), 1).is_err());
+
+        assert_eq!(_nftoken.balance_of(alice()), 3);
+        assert_eq!(_nftoken.is_approved(1, alice()), false);
+    }
+
+    #[test]
+    fn supply_counters_do_not_overflow() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // a mint running past the end of the id space is refused without side effects
+        assert!(_nftoken.mint(bob(), TokenCount::max_value()).is_err());
+
+        assert_eq!(_nftoken.total_minted(), 1);
+        assert_eq!(_nftoken.balance_of(alice()), 1);
+        assert_eq!(_nftoken.balance_of(bob()), 0);
+
+        // the largest mint that still fits the id space succeeds exactly once
+        assert_eq!(_nftoken.mint(bob(), TokenCount::max_value() - 1), Ok((2, TokenCount::max_value())));
+        assert_eq!(_nftoken.mint(bob(), 1), Err(Error::Overflow));
+        assert_eq!(_nftoken.total_minted(), TokenCount::max_value());
+        assert_eq!(_nftoken.balance_of(bob()), TokenCount::max_value() - 1);
+        assert_eq!(_nftoken.token_of_owner_by_index(bob(), TokenCount::max_value() - 2), Some(TokenCount::max_value()));
+    }
+
+    #[test]
+    fn approvals_of_tracks_approve_revoke_and_transfer() {
+        let mut _nftoken = deploy!(Fixture::new(4));
+
+        _nftoken.approval(bob(), 1, true).unwrap();
+        _nftoken.approval(charlie(), 2, true).unwrap();
+        _nftoken.approval(bob(), 3, true).unwrap();
+        assert_eq!(_nftoken.approvals_of(alice(), 0, 10), vec![(1, bob()), (2, charlie()), (3, bob())]);
+        assert_eq!(_nftoken.approvals_of(alice(), 1, 1), vec![(2, charlie())]);
+
+        // revoking and transferring both drop the approval from the index
+        _nftoken.approval(charlie(), 2, false).unwrap();
+        _nftoken.transfer(charlie(), 3).unwrap();
+        assert_eq!(_nftoken.approvals_of(alice(), 0, 10), vec![(1, bob())]);
+        assert_eq!(_nftoken.is_approved(3, bob()), false);
+
+        // bulk revocation is refused if any token is not the caller's
+        assert!(_nftoken.revoke_approvals(vec![1, 3]).is_err());
+        assert_eq!(_nftoken.revoke_approvals(vec![1, 4]), Ok(()));
+        assert_eq!(_nftoken.approvals_of(alice(), 0, 10), vec![]);
+    }
+
+    /// Asserts that an owner's enumeration lists exactly balance_of(owner) tokens, all owned by them
+    macro_rules! assert_enumeration_consistent {
+        ($nftoken:expr, $owner:expr) => {
+            let balance = $nftoken.balance_of($owner);
+            for index in 0..balance {
+                let token_id = $nftoken.token_of_owner_by_index($owner, index);
+                assert!(token_id.is_some());
+                assert_eq!($nftoken.owner_of(token_id.unwrap()), Some($owner));
+            }
+            assert_eq!($nftoken.token_of_owner_by_index($owner, balance), None);
+        };
+    }
+
+    #[test]
+    fn owner_enumeration_stays_consistent() {
+        let mut _nftoken = deploy!(Fixture::new(5));
+        _nftoken.mint(bob(), 2).unwrap();
+
+        assert_eq!(_nftoken.token_of_owner_by_index(alice(), 0), Some(1));
+        assert_eq!(_nftoken.token_of_owner_by_index(bob(), 1), Some(7));
+
+        // transfers out of the middle and the end of alice's holdings
+        _nftoken.transfer(bob(), 2).unwrap();
+        _nftoken.transfer(bob(), 4).unwrap();
+        assert_enumeration_consistent!(_nftoken, alice());
+        assert_enumeration_consistent!(_nftoken, bob());
+
+        // burning through fusion shrinks the enumeration too
+        _nftoken.fuse(1, 3).unwrap();
+        assert_enumeration_consistent!(_nftoken, alice());
+        assert_eq!(_nftoken.balance_of(alice()), 2);
+    }
+
+    #[test]
+    fn airdrop_resumes_from_cursor() {
+        let zero = account(0x0);
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // a list larger than one batch takes two calls
+        let mut recipients = vec![bob(); MAX_AIRDROP_BATCH as usize + 1];
+        recipients[1] = zero;
+        let cursor = _nftoken.airdrop(recipients.clone(), 0).unwrap();
+        assert_eq!(cursor, MAX_AIRDROP_BATCH);
+        assert_eq!(_nftoken.airdrop(recipients.clone(), cursor), Ok(MAX_AIRDROP_BATCH + 1));
+        assert_eq!(_nftoken.airdrop(recipients.clone(), MAX_AIRDROP_BATCH + 1), Ok(MAX_AIRDROP_BATCH + 1));
+
+        // the zero account was skipped
+        assert_eq!(_nftoken.balance_of(bob()), MAX_AIRDROP_BATCH as TokenCount);
+        assert_eq!(_nftoken.total_minted(), MAX_AIRDROP_BATCH as TokenCount + 1);
+    }
+
+    #[test]
+    fn refused_airdrops_mint_nothing_and_keep_the_cursor() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // bob appears twice, which would take him over the cap
+        assert_eq!(_nftoken.set_max_per_holder(1), Ok(()));
+        assert_eq!(_nftoken.airdrop(vec![charlie(), bob(), bob()], 0), Err(Error::HolderCapExceeded));
+        assert_eq!(_nftoken.total_minted(), 1);
+
+        assert_eq!(_nftoken.set_max_per_holder(0), Ok(()));
+        assert_eq!(_nftoken.set_paused(true), Ok(()));
+        assert_eq!(_nftoken.airdrop(vec![charlie(), bob(), bob()], 0), Err(Error::Paused));
+        assert_eq!(_nftoken.total_minted(), 1);
+
+        assert_eq!(_nftoken.set_paused(false), Ok(()));
+        assert_eq!(_nftoken.airdrop(vec![charlie(), bob(), bob()], 0), Ok(3));
+        assert_eq!(_nftoken.balance_of(bob()), 2);
+    }
+
+    #[test]
+    fn balances_are_frozen_at_snapshots() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+
+        assert_eq!(_nftoken.snapshot(), Ok(1));
+        _nftoken.transfer(bob(), 1).unwrap();
+        _nftoken.mint(bob(), 2).unwrap();
+
+        assert_eq!(_nftoken.snapshot(), Ok(2));
+        _nftoken.transfer(bob(), 2).unwrap();
+
+        // snapshot 1 predates every change, snapshot 2 only the last transfer
+        assert_eq!(_nftoken.balance_of_at(alice(), 1), 3);
+        assert_eq!(_nftoken.balance_of_at(bob(), 1), 0);
+        assert_eq!(_nftoken.total_supply_at(1), 3);
+        assert_eq!(_nftoken.balance_of_at(alice(), 2), 2);
+        assert_eq!(_nftoken.balance_of_at(bob(), 2), 3);
+        assert_eq!(_nftoken.total_supply_at(2), 5);
+
+        // unknown snapshots have no balances
+        assert_eq!(_nftoken.balance_of_at(alice(), 3), 0);
+        assert_eq!(_nftoken.balance_of(alice()), 1);
+    }
+
+    #[test]
+    fn delegated_votes_follow_transfers() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+
+        // holders vote with their own tokens until they delegate
+        assert_eq!(_nftoken.voting_power_of(alice()), 3);
+        assert_eq!(_nftoken.delegate(charlie()), Ok(()));
+        assert_eq!(_nftoken.delegate_of(alice()), charlie());
+        assert_eq!(_nftoken.voting_power_of(alice()), 0);
+        assert_eq!(_nftoken.voting_power_of(charlie()), 3);
+
+        // a transfer moves the votes from charlie to bob
+        _nftoken.transfer(bob(), 1).unwrap();
+        assert_eq!(_nftoken.voting_power_of(charlie()), 2);
+        assert_eq!(_nftoken.voting_power_of(bob()), 1);
+
+        // delegating back to oneself restores the default
+        assert_eq!(_nftoken.delegate(alice()), Ok(()));
+        assert_eq!(_nftoken.voting_power_of(alice()), 2);
+        assert_eq!(_nftoken.voting_power_of(charlie()), 0);
+    }
+
+    #[test]
+    fn holders_govern_admin_actions() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+        _nftoken.mint(bob(), 2).unwrap();
+        assert_eq!(_nftoken.set_governance(10, 5_000), Ok(()));
+
+        // bob proposes turning the operator filter on; votes are frozen at the proposal's snapshot
+        set_caller(bob());
+        assert_eq!(_nftoken.propose(Action::SetOperatorFilter(true)), Ok(1));
+        assert_eq!(_nftoken.vote(1, true), Ok(()));
+        assert!(_nftoken.vote(1, true).is_err());
+
+        // tokens received after the snapshot carry no votes
+        set_caller(alice());
+        _nftoken.transfer(bob(), 1).unwrap();
+        assert_eq!(_nftoken.vote(1, false), Ok(()));
+
+        // alice's 3 votes against outweigh bob's 2 in favour
+        set_block_number(11);
+        assert!(_nftoken.execute(1).is_err());
+        assert_eq!(_nftoken.proposal_of(1).unwrap().votes_against, 3);
+
+        // a proposal backed by a majority above quorum is scheduled once, then carried out
+        set_caller(bob());
+        assert_eq!(_nftoken.propose(Action::SetPaused(true)), Ok(2));
+        assert_eq!(_nftoken.vote(2, true), Ok(()));
+        assert!(_nftoken.execute(2).is_err());
+        set_block_number(22);
+        assert_eq!(_nftoken.execute(2), Ok(()));
+        assert!(_nftoken.execute(2).is_err());
+        assert_eq!(_nftoken.paused(), false);
+        assert_eq!(_nftoken.execute_scheduled(1), Ok(()));
+        assert_eq!(_nftoken.paused(), true);
+    }
+
+    #[test]
+    fn tokens_bridge_out_and_back_in() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+        assert_eq!(_nftoken.set_bridge(Some(eve())), Ok(()));
+
+        // the bridge needs the owner's approval to take a token out
+        set_caller(eve());
+        assert!(_nftoken.bridge_out(2, 7, vec![0x9; 32]).is_err());
+        set_caller(alice());
+        _nftoken.approval(eve(), 2, true).unwrap();
+        set_caller(eve());
+        assert_eq!(_nftoken.bridge_out(2, 7, vec![0x9; 32]), Ok(()));
+        assert_eq!(_nftoken.owner_of(2), None);
+        assert_eq!(_nftoken.owner_of(3), Some(alice()));
+        assert_eq!(_nftoken.is_bridged_out(2), true);
+
+        // only bridged-out tokens come back, and only once
+        assert!(_nftoken.bridge_in(3, bob(), vec![]).is_err());
+        assert_eq!(_nftoken.bridge_in(2, bob(), vec![0x1]), Ok(()));
+        assert!(_nftoken.bridge_in(2, bob(), vec![0x1]).is_err());
+        assert_eq!(_nftoken.owner_of(2), Some(bob()));
+        assert_eq!(_nftoken.balance_of(bob()), 1);
+        assert_eq!(_nftoken.total_burned(), 0);
+    }
+
+    #[test]
+    fn bridge_custody_locks_and_unlocks() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+        _nftoken.set_bridge(Some(eve())).unwrap();
+        _nftoken.set_approval_for_all(eve(), true).unwrap();
+
+        // a token in custody cannot be moved by its owner
+        set_caller(eve());
+        assert_eq!(_nftoken.bridge_lock(1, 7, vec![0x9; 32]), Ok(()));
+        assert_eq!(_nftoken.bridge_lock_of(1), Some((7, vec![0x9; 32])));
+        set_caller(alice());
+        assert!(_nftoken.transfer(bob(), 1).is_err());
+
+        // a failed remote transfer hands the token back to its owner
+        set_caller(eve());
+        assert_eq!(_nftoken.bridge_unlock(1), Ok(()));
+        assert_eq!(_nftoken.bridge_lock_of(1), None);
+
+        // a token returning from the remote chain is released to its new owner
+        assert_eq!(_nftoken.bridge_lock(2, 7, vec![0x9; 32]), Ok(()));
+        assert_eq!(_nftoken.bridge_release(2, bob()), Ok(()));
+        assert_eq!(_nftoken.owner_of(2), Some(bob()));
+        assert!(_nftoken.bridge_unlock(2).is_err());
+    }
+
+    #[test]
+    fn signers_carry_out_admin_requests() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // thresholds out of range and repeated signers are refused
+        assert!(_nftoken.set_signers(vec![alice(), bob()], 3).is_err());
+        assert!(_nftoken.set_signers(vec![alice(), alice()], 1).is_err());
+        assert_eq!(_nftoken.set_signers(vec![alice(), bob(), charlie()], 2), Ok(()));
+
+        // only signers submit, and the action waits for a second confirmation
+        set_caller(dave());
+        assert!(_nftoken.submit_admin_request(Action::SetFusionCooldown(42)).is_err());
+        set_caller(bob());
+        assert_eq!(_nftoken.submit_admin_request(Action::SetFusionCooldown(42)), Ok(1));
+        assert_eq!(_nftoken.confirmations_of(1), 1);
+        assert!(_nftoken.confirm_admin_request(1).is_err());
+        assert_eq!(_nftoken.admin_request_of(1).unwrap().executed, false);
+
+        set_caller(charlie());
+        assert_eq!(_nftoken.confirm_admin_request(1), Ok(()));
+        assert_eq!(_nftoken.admin_request_of(1).unwrap().executed, true);
+        assert_eq!(_nftoken.scheduled_of(1).unwrap().action, Action::SetFusionCooldown(42));
+
+        // an executed request cannot be confirmed again
+        set_caller(alice());
+        assert!(_nftoken.confirm_admin_request(1).is_err());
+
+        // ownership can be handed over by the signers, once the scheduled action is carried out
+        assert_eq!(_nftoken.submit_admin_request(Action::TransferOwnership(dave())), Ok(2));
+        set_caller(bob());
+        assert_eq!(_nftoken.confirm_admin_request(2), Ok(()));
+        assert_eq!(_nftoken.owner(), alice());
+        assert_eq!(_nftoken.execute_scheduled(2), Ok(()));
+        assert_eq!(_nftoken.owner(), dave());
+    }
+
+    #[test]
+    fn scheduled_actions_wait_for_the_timelock() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // without a delay a scheduled action can be carried out straight away
+        set_block_number(1);
+        assert_eq!(_nftoken.schedule(Action::SetTimelockDelay(10)), Ok(1));
+        assert_eq!(_nftoken.execute_scheduled(1), Ok(()));
+        assert_eq!(_nftoken.timelock_delay(), 10);
+
+        // with a delay in place changes cannot be made directly
+        assert!(_nftoken.set_fusion_cooldown(42).is_err());
+
+        assert_eq!(_nftoken.schedule(Action::SetFusionCooldown(42)), Ok(2));
+        assert_eq!(_nftoken.scheduled_of(2).unwrap().eta, 11);
+        set_block_number(10);
+        assert!(_nftoken.execute_scheduled(2).is_err());
+        set_block_number(11);
+        assert_eq!(_nftoken.execute_scheduled(2), Ok(()));
+        assert!(_nftoken.execute_scheduled(2).is_err());
+
+        // a cancelled action can no longer be carried out
+        assert_eq!(_nftoken.schedule(Action::SetTimelockDelay(0)), Ok(3));
+        assert_eq!(_nftoken.cancel_scheduled(3), Ok(()));
+        set_block_number(30);
+        assert!(_nftoken.execute_scheduled(3).is_err());
+        assert_eq!(_nftoken.timelock_delay(), 10);
+    }
+
+    #[test]
+    fn minter_role_records_mint_provenance() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // once a minter is set the owner can no longer mint
+        assert_eq!(_nftoken.set_minter(Some(bob())), Ok(()));
+        assert!(_nftoken.mint(alice(), 1).is_err());
+
+        set_caller(bob());
+        assert_eq!(_nftoken.mint_with_proposal_ref(charlie(), 3, 7), Ok((2, 4)));
+        assert_eq!(_nftoken.mint_authorization_of(3), Some((bob(), 7)));
+        assert_eq!(_nftoken.mint_authorization_of(1), None);
+
+        // the role can be handed back to the owner
+        set_caller(alice());
+        assert_eq!(_nftoken.set_minter(None), Ok(()));
+        assert_eq!(_nftoken.mint(alice(), 1), Ok((5, 5)));
+    }
+
+    #[test]
+    fn history_keeps_the_latest_transfers() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+        assert_eq!(_nftoken.set_history_length(2), Ok(()));
+
+        set_block_number(1);
+        assert_eq!(_nftoken.transfer(bob(), 1), Ok(()));
+        set_caller(bob());
+        set_block_number(2);
+        assert_eq!(_nftoken.transfer(charlie(), 1), Ok(()));
+        set_caller(charlie());
+        set_block_number(3);
+        assert_eq!(_nftoken.transfer(alice(), 1), Ok(()));
+
+        // only the two latest transfers are kept
+        assert_eq!(_nftoken.history_of(1), vec![(bob(), charlie(), 2), (charlie(), alice(), 3)]);
+
+        // shortening the history applies straight away
+        set_caller(alice());
+        assert_eq!(_nftoken.set_history_length(1), Ok(()));
+        assert_eq!(_nftoken.history_of(1), vec![(charlie(), alice(), 3)]);
+    }
+
+    #[test]
+    fn transfer_count_follows_every_change_of_hands() {
+        let mut _nftoken = deploy!(Fixture::new(2));
+        assert_eq!(_nftoken.transfer_count(1), 0);
+
+        assert_eq!(_nftoken.transfer(bob(), 1), Ok(()));
+        set_caller(bob());
+        assert_eq!(_nftoken.transfer(alice(), 1), Ok(()));
+
+        // failed transfers are not counted
+        assert!(_nftoken.transfer(alice(), 2).is_err());
+        assert_eq!(_nftoken.transfer_count(1), 2);
+        assert_eq!(_nftoken.transfer_count(2), 0);
+    }
+
+    #[test]
+    fn provenance_records_minter_and_block() {
+        set_block_number(3);
+        let mut _nftoken = deploy!(Fixture::new(2));
+
+        set_block_number(8);
+        assert_eq!(_nftoken.mint(bob(), 2), Ok((3, 4)));
+
+        // provenance stays with the token when it changes hands
+        assert_eq!(_nftoken.transfer(bob(), 2), Ok(()));
+        assert_eq!(_nftoken.provenance_of(2), Some((alice(), 3)));
+        assert_eq!(_nftoken.provenance_of(4), Some((alice(), 8)));
+        assert_eq!(_nftoken.provenance_of(5), None);
+    }
+
+    #[test]
+    fn holder_count_tracks_first_and_last_token() {
+        let mut _nftoken = deploy!(Fixture::new(2));
+        assert_eq!(_nftoken.holder_count(), 1);
+
+        assert_eq!(_nftoken.transfer(bob(), 1), Ok(()));
+        assert_eq!(_nftoken.holder_count(), 2);
+
+        // a second token does not count its holder twice
+        assert_eq!(_nftoken.transfer(bob(), 2), Ok(()));
+        assert_eq!(_nftoken.holder_count(), 1);
+    }
+
+    #[test]
+    fn rarity_scores_are_committed_once() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+
+        // unminted tokens and mismatched scores are refused
+        assert!(_nftoken.commit_rarity(vec![1, 4], vec![10, 20]).is_err());
+        assert!(_nftoken.commit_rarity(vec![1, 2], vec![10]).is_err());
+
+        assert_eq!(_nftoken.commit_rarity(vec![1, 2], vec![10, 20]), Ok(()));
+        assert_eq!(_nftoken.rarity_of(2), Some(20));
+        assert_eq!(_nftoken.rarity_of(3), None);
+
+        // a committed score cannot be changed
+        assert!(_nftoken.commit_rarity(vec![2, 3], vec![99, 30]).is_err());
+        assert_eq!(_nftoken.rarity_of(2), Some(20));
+
+        set_caller(bob());
+        assert!(_nftoken.commit_rarity(vec![3], vec![30]).is_err());
+    }
+
+    #[test]
+    fn asset_uris_are_validated() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+
+        // CIDv0 and CIDv1 IPFS URIs are accepted
+        let v0 = b"ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_vec();
+        let v1 = b"ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi/1.json".to_vec();
+        assert_eq!(_nftoken.propose_asset(1, v0, 0), Ok(()));
+        assert_eq!(_nftoken.propose_asset(1, v1, 1), Ok(()));
+
+        // typo'd CIDs and other schemes are refused
+        let typo = b"ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbd0".to_vec();
+        let http = b"https://example.com/1.json".to_vec();
+        assert!(_nftoken.propose_asset(1, typo, 2).is_err());
+        assert!(_nftoken.propose_asset(1, http.clone(), 2).is_err());
+
+        // until their scheme is allowed
+        assert_eq!(_nftoken.set_allowed_uri_schemes(vec![b"https".to_vec()]), Ok(()));
+        assert_eq!(_nftoken.propose_asset(1, http, 2), Ok(()));
+        assert!(_nftoken.propose_asset(1, b"https://".to_vec(), 3).is_err());
+    }
+
+    #[test]
+    fn oracle_updates_dynamic_tokens_at_a_limited_rate() {
+        let mut _nftoken = deploy!(Fixture::new(2));
+        assert_eq!(_nftoken.set_oracle(Some(bob()), 10), Ok(()));
+        assert_eq!(_nftoken.set_dynamic(1, true), Ok(()));
+
+        // the owner has no write access of its own
+        let update = vec![(b"goals".to_vec(), b"3".to_vec())];
+        assert!(_nftoken.push_attributes(1, update.clone()).is_err());
+
+        set_caller(bob());
+        set_block_number(5);
+        assert_eq!(_nftoken.push_attributes(1, update.clone()), Ok(()));
+        assert_eq!(_nftoken.attribute_of(1, b"goals".to_vec()), b"3".to_vec());
+
+        // tokens that are not dynamic cannot be updated
+        assert_eq!(_nftoken.is_dynamic(2), false);
+        assert!(_nftoken.push_attributes(2, update.clone()).is_err());
+
+        // updates of a token are limited to one per interval
+        let update = vec![(b"goals".to_vec(), b"4".to_vec())];
+        set_block_number(14);
+        assert!(_nftoken.push_attributes(1, update.clone()).is_err());
+        set_block_number(15);
+        assert_eq!(_nftoken.push_attributes(1, update), Ok(()));
+        assert_eq!(_nftoken.attribute_of(1, b"goals".to_vec()), b"4".to_vec());
+    }
+
+    #[test]
+    fn reserve_is_only_minted_through_mint_reserved() {
+        let mut _nftoken = deploy!(Fixture::new(2));
+
+        // a reserve cannot exceed what is left below the max supply, and needs a max supply
+        assert!(_nftoken.set_reserve(10, 9).is_err());
+        assert_eq!(_nftoken.set_reserve(0, 3), Err(Error::InvalidInput));
+        assert_eq!(_nftoken.set_reserve(0, 0), Ok(()));
+        assert_eq!(_nftoken.set_reserve(10, 3), Ok(()));
+
+        // public mints stop where the reserve begins
+        assert!(_nftoken.mint(bob(), 6).is_err());
+        assert_eq!(_nftoken.mint(bob(), 5), Ok((3, 7)));
+        assert!(_nftoken.mint(bob(), 1).is_err());
+
+        assert!(_nftoken.mint_reserved(alice(), 4).is_err());
+        assert_eq!(_nftoken.mint_reserved(alice(), 2), Ok((8, 9)));
+        assert_eq!(_nftoken.remaining_reserve(), 1);
+
+        // the minter role does not extend to the reserve
+        assert_eq!(_nftoken.set_minter(Some(bob())), Ok(()));
+        set_caller(bob());
+        assert!(_nftoken.mint_reserved(bob(), 1).is_err());
+    }
+
+    #[test]
+    fn guardians_recover_tokens_after_the_challenge_delay() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+        assert_eq!(_nftoken.set_guardians(vec![bob(), charlie()], 2, 10), Ok(()));
+
+        // guardians have to agree on the recovery address; one backing another address starts nothing
+        set_block_number(1);
+        set_caller(bob());
+        assert_eq!(_nftoken.support_recovery(alice(), dave()), Ok(()));
+        set_caller(charlie());
+        assert_eq!(_nftoken.support_recovery(alice(), bob()), Ok(()));
+        assert_eq!(_nftoken.recovery_of(alice()).unwrap().ready_at, None);
+
+        // the holder can veto a recovery they did not ask for
+        assert_eq!(_nftoken.support_recovery(alice(), dave()), Ok(()));
+        assert_eq!(_nftoken.recovery_of(alice()).unwrap().ready_at, Some(11));
+        set_caller(alice());
+        assert_eq!(_nftoken.veto_recovery(), Ok(()));
+        assert!(_nftoken.complete_recovery(alice()).is_err());
+
+        set_caller(bob());
+        assert_eq!(_nftoken.support_recovery(alice(), dave()), Ok(()));
+        set_caller(charlie());
+        assert_eq!(_nftoken.support_recovery(alice(), dave()), Ok(()));
+
+        // tokens move only once the delay has passed
+        set_block_number(10);
+        assert!(_nftoken.complete_recovery(alice()).is_err());
+        set_block_number(11);
+        assert_eq!(_nftoken.complete_recovery(alice()), Ok(()));
+        assert_eq!(_nftoken.balance_of(alice()), 0);
+        assert_eq!(_nftoken.balance_of(dave()), 3);
+        assert_eq!(_nftoken.owner_of(2), Some(dave()));
+    }
+
+    #[test]
+    fn backup_sweeps_tokens_after_announcing() {
+        let mut _nftoken = deploy!(Fixture::new(2));
+        assert_eq!(_nftoken.set_backup(Some(bob()), 5), Ok(()));
+
+        // only the backup can announce, and sweeping waits for the delay
+        set_block_number(1);
+        set_caller(charlie());
+        assert!(_nftoken.announce_recovery(alice()).is_err());
+        set_caller(bob());
+        assert!(_nftoken.recover_all(alice()).is_err());
+        assert_eq!(_nftoken.announce_recovery(alice()), Ok(()));
+        assert_eq!(_nftoken.backup_ready_at(alice()), Some(6));
+
+        // the holder can still cancel while they have their keys
+        set_caller(alice());
+        assert_eq!(_nftoken.cancel_backup_recovery(), Ok(()));
+        set_caller(bob());
+        set_block_number(6);
+        assert!(_nftoken.recover_all(alice()).is_err());
+
+        assert_eq!(_nftoken.announce_recovery(alice()), Ok(()));
+        set_block_number(10);
+        assert!(_nftoken.recover_all(alice()).is_err());
+        set_block_number(11);
+        assert_eq!(_nftoken.recover_all(alice()), Ok(()));
+        assert_eq!(_nftoken.balance_of(bob()), 2);
+        assert_eq!(_nftoken.balance_of(alice()), 0);
+    }
+
+    #[test]
+    fn fresh_mints_wait_out_the_transfer_cooldown() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+        assert_eq!(_nftoken.set_transfer_cooldown(5), Ok(()));
+
+        set_block_number(10);
+        assert_eq!(_nftoken.mint(alice(), 1), Ok((2, 2)));
+        assert_eq!(_nftoken.transferable_at(2), 15);
+
+        set_block_number(14);
+        assert!(_nftoken.transfer(bob(), 2).is_err());
+        // tokens minted earlier are already past their cooldown
+        assert_eq!(_nftoken.transfer(bob(), 1), Ok(()));
+
+        set_block_number(15);
+        assert_eq!(_nftoken.transfer(bob(), 2), Ok(()));
+    }
+
+    #[test]
+    fn holdings_are_capped_per_account() {
+        let mut _nftoken = deploy!(Fixture::new(4));
+        assert_eq!(_nftoken.set_max_per_holder(2), Ok(()));
+
+        // accounts above the cap keep what they hold but cannot receive more
+        assert!(_nftoken.mint(alice(), 1).is_err());
+        assert!(_nftoken.mint(bob(), 3).is_err());
+        assert_eq!(_nftoken.mint(bob(), 1), Ok((5, 5)));
+
+        assert_eq!(_nftoken.transfer(bob(), 1), Ok(()));
+        assert!(_nftoken.transfer(bob(), 2).is_err());
+        assert_eq!(_nftoken.balance_of(bob()), 2);
+    }
+
+    #[test]
+    fn operator_filter_limits_approvals() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+        assert_eq!(_nftoken.set_approval_for_all(dave(), true), Ok(()));
+        assert_eq!(_nftoken.set_operator_filter(true), Ok(()));
+        assert_eq!(_nftoken.register_operator(bob(), true), Ok(()));
+
+        // unregistered operators can neither be approved nor use earlier approvals
+        assert!(_nftoken.set_approval_for_all(charlie(), true).is_err());
+        assert_eq!(_nftoken.set_approval_for_all(bob(), true), Ok(()));
+        set_caller(dave());
+        assert!(_nftoken.transfer_from(dave(), 1).is_err());
+
+        set_caller(bob());
+        assert_eq!(_nftoken.transfer_from(bob(), 1), Ok(()));
+
+        // single token approvals are filtered the same way, including those given before
+        set_caller(alice());
+        assert_eq!(_nftoken.approval(charlie(), 2, true), Err(Error::Unauthorized));
+        assert_eq!(_nftoken.set_operator_filter(false), Ok(()));
+        assert_eq!(_nftoken.approval(charlie(), 2, true), Ok(()));
+        assert_eq!(_nftoken.set_operator_filter(true), Ok(()));
+        set_caller(charlie());
+        assert_eq!(_nftoken.transfer_from(charlie(), 2), Err(Error::NotApproved));
+    }
+
+    #[test]
+    fn super_admin_moves_tokens_only_while_paused() {
+        let reason = Hash::from([0x7; 32]);
+        let mut _nftoken = deploy!(Fixture::new(2));
+        assert_eq!(_nftoken.set_super_admin(Some(charlie())), Ok(()));
+
+        set_caller(charlie());
+        assert!(_nftoken.admin_transfer(1, bob(), reason).is_err());
+
+        // pausing halts regular token movements
+        set_caller(alice());
+        assert_eq!(_nftoken.set_paused(true), Ok(()));
+        assert!(_nftoken.transfer(bob(), 1).is_err());
+        assert!(_nftoken.mint(alice(), 1).is_err());
+
+        // only the super admin can move tokens while paused
+        assert!(_nftoken.admin_transfer(1, bob(), reason).is_err());
+        set_caller(charlie());
+        assert_eq!(_nftoken.admin_transfer(1, bob(), reason), Ok(()));
+        assert_eq!(_nftoken.owner_of(1), Some(bob()));
+
+        set_caller(alice());
+        assert_eq!(_nftoken.set_paused(false), Ok(()));
+        assert_eq!(_nftoken.transfer(bob(), 2), Ok(()));
+    }
+
+    #[test]
+    fn operator_allowance_counts_down_with_each_transfer() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+        assert_eq!(_nftoken.approve_transfers(bob(), 2), Ok(()));
+
+        set_caller(bob());
+        assert_eq!(_nftoken.transfer_from(charlie(), 1), Ok(()));
+        assert_eq!(_nftoken.remaining_allowance(alice(), bob()), 1);
+
+        // a failed transfer does not use up the allowance
+        assert!(_nftoken.transfer_from(alice(), 2).is_err());
+        assert_eq!(_nftoken.transfer_from(charlie(), 2), Ok(()));
+        assert_eq!(_nftoken.remaining_allowance(alice(), bob()), 0);
+        assert!(_nftoken.transfer_from(charlie(), 3).is_err());
+        assert_eq!(_nftoken.owner_of(3), Some(alice()));
+    }
+
+    #[test]
+    fn range_approvals_cover_contiguous_ids() {
+        let mut _nftoken = deploy!(Fixture::new(5));
+        assert_eq!(_nftoken.approve_range(bob(), 2, 4), Ok(()));
+        assert!(_nftoken.approve_range(bob(), 4, 2).is_err());
+
+        set_caller(bob());
+        assert!(_nftoken.transfer_from(charlie(), 1).is_err());
+        assert_eq!(_nftoken.transfer_from(charlie(), 2), Ok(()));
+        assert!(_nftoken.transfer_from(charlie(), 5).is_err());
+
+        // revoked ranges no longer authorize transfers
+        set_caller(alice());
+        assert_eq!(_nftoken.revoke_range(bob(), 2, 4), Ok(()));
+        assert_eq!(_nftoken.range_approvals_of(alice()), vec![]);
+        set_caller(bob());
+        assert!(_nftoken.transfer_from(charlie(), 3).is_err());
+    }
+
+    #[test]
+    fn invalidating_moves_the_nonce_on() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+        assert_eq!(_nftoken.nonce_of(alice()), 0);
+
+        assert_eq!(_nftoken.invalidate_nonce(), Ok(()));
+        assert_eq!(_nftoken.invalidate_nonce(), Ok(()));
+        assert_eq!(_nftoken.nonce_of(alice()), 2);
+        assert_eq!(_nftoken.nonce_of(bob()), 0);
+    }
+
+    #[test]
+    fn failures_report_their_reason() {
+        let zero = account(0x0);
+        let mut _nftoken = deploy!(Fixture::new(2));
+
+        assert_eq!(_nftoken.transfer_from(bob(), 3), Err(Error::TokenNotFound));
+        assert_eq!(_nftoken.transfer(zero, 1), Err(Error::ZeroAccount));
+        assert_eq!(_nftoken.transfer(alice(), 1), Err(Error::SelfTransfer));
+        assert_eq!(_nftoken.approval(alice(), 1, true), Err(Error::SelfApproval));
+        assert_eq!(_nftoken.mint(bob(), 0), Err(Error::ZeroAmount));
+
+        set_caller(bob());
+        assert_eq!(_nftoken.mint(bob(), 1), Err(Error::Unauthorized));
+        assert_eq!(_nftoken.approval(charlie(), 1, true), Err(Error::NotOwner));
+        assert_eq!(_nftoken.transfer_from(charlie(), 1), Err(Error::NotApproved));
+
+        set_caller(alice());
+        assert_eq!(_nftoken.set_paused(true), Ok(()));
+        assert_eq!(_nftoken.transfer(bob(), 1), Err(Error::Paused));
+    }
+
+    #[test]
+    fn refused_fusions_leave_the_parents_in_place() {
+        let mut _nftoken = deploy!(Fixture::new(2));
+        assert_eq!(_nftoken.set_paused(true), Ok(()));
+
+        assert_eq!(_nftoken.fuse(1, 2), Err(Error::Paused));
+        assert_eq!(_nftoken.balance_of(alice()), 2);
+        assert_eq!(_nftoken.total_burned(), 0);
+    }
+
+    #[test]
+    fn fusions_over_a_lowered_cap_leave_the_parents_in_place() {
+        let mut _nftoken = deploy!(Fixture::new(4));
+        assert_eq!(_nftoken.set_max_per_holder(2), Ok(()));
+
+        // alice keeps her 4 tokens above the cap, and a fusion would still leave her with 3
+        assert_eq!(_nftoken.fuse(1, 2), Err(Error::HolderCapExceeded));
+        assert_eq!(_nftoken.balance_of(alice()), 4);
+        assert_eq!(_nftoken.total_burned(), 0);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Mint(usize, TokenCount),
+        Transfer(usize, usize, u64),
+        Approve(usize, usize, u64),
+        TransferFrom(usize, usize, u64),
+        Burn(usize, u64),
+    }
+
+    /// Operations between four accounts on the first few dozen token ids, so that
+    /// sequences hit both valid and refused calls
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            (0..4usize, 1..4 as TokenCount).prop_map(|(to, value)| Op::Mint(to, value)),
+            (0..4usize, 0..4usize, 1..24u64).prop_map(|(caller, to, token_id)| Op::Transfer(caller, to, token_id)),
+            (0..4usize, 0..4usize, 1..24u64).prop_map(|(caller, spender, token_id)| Op::Approve(caller, spender, token_id)),
+            (0..4usize, 0..4usize, 1..24u64).prop_map(|(caller, to, token_id)| Op::TransferFrom(caller, to, token_id)),
+            (0..4usize, 1..24u64).prop_map(|(caller, token_id)| Op::Burn(caller, token_id)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn random_operations_keep_the_books_balanced(ops in proptest::collection::vec(op(), 1..40)) {
+            let accounts = [alice(), bob(), charlie(), dave()];
+            let mut _nftoken = deploy!(Fixture::new(4));
+            // burns go through a burning redemption campaign, the one way holders can burn
+            assert_eq!(_nftoken.create_campaign(true), Ok(()));
+
+            for op in ops {
+                // refused calls are expected; only the state they leave behind is checked
+                let _ = match op {
+                    Op::Mint(to, value) => {
+                        set_caller(accounts[0]);
+                        _nftoken.mint(accounts[to], value).map(|_| ())
+                    }
+                    Op::Transfer(caller, to, token_id) => {
+                        set_caller(accounts[caller]);
+                        _nftoken.transfer(accounts[to], token_id)
+                    }
+                    Op::Approve(caller, spender, token_id) => {
+                        set_caller(accounts[caller]);
+                        _nftoken.approval(accounts[spender], token_id, true)
+                    }
+                    Op::TransferFrom(caller, to, token_id) => {
+                        set_caller(accounts[caller]);
+                        _nftoken.transfer_from(accounts[to], token_id)
+                    }
+                    Op::Burn(caller, token_id) => {
+                        set_caller(accounts[caller]);
+                        _nftoken.redeem(token_id, 1)
+                    }
+                };
+
+                // the balances add up to the live supply
+                let live_supply = _nftoken.total_minted() - _nftoken.total_burned();
+                let balances: TokenCount = accounts.iter().map(|account| _nftoken.balance_of(*account)).sum();
+                prop_assert_eq!(balances, live_supply);
+
+                // the owner index agrees with owner_of
+                for account in accounts.iter() {
+                    for index in 0.._nftoken.balance_of(*account) {
+                        let token_id = _nftoken.token_of_owner_by_index(*account, index);
+                        prop_assert!(token_id.is_some());
+                        prop_assert_eq!(_nftoken.owner_of(token_id.unwrap()), Some(*account));
+                    }
+                }
+
+                // burned tokens keep no approvals
+                for token_id in 1..=_nftoken.total_minted() {
+                    if _nftoken.owner_of(token_id).is_none() {
+                        for account in accounts.iter() {
+                            prop_assert!(!_nftoken.is_approved(token_id, *account));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transfer_from_follows_the_caller() {
+        set_block_number(3);
+        let mut _nftoken = deploy!(Fixture::new(2));
+        assert_eq!(_nftoken.approval(bob(), 1, true), Ok(()));
+
+        // only the approved account may move the token on the owner's behalf
+        assert_eq!(as_caller(charlie(), || _nftoken.transfer_from(charlie(), 1)), Err(Error::NotApproved));
+        assert_eq!(as_caller(bob(), || _nftoken.transfer_from(charlie(), 1)), Ok(()));
+        assert_eq!(_nftoken.owner_of(1), Some(charlie()));
+
+        // the block moves on between calls just as the caller does
+        set_block_number(7);
+        assert_eq!(as_caller(charlie(), || _nftoken.transfer(alice(), 1)), Ok(()));
+        assert_eq!(_nftoken.history_of(1), vec![(alice(), charlie(), 3), (charlie(), alice(), 7)]);
+    }
+
+    #[test]
+    fn fixtures_deal_tokens_in_turn() {
+        let mut _nftoken = deploy!(Fixture::new(7).holders(3).approve(5, dave()));
+        assert_eq!(_nftoken.balance_of(alice()), 3);
+        assert_eq!(_nftoken.balance_of(bob()), 2);
+        assert_eq!(_nftoken.balance_of(charlie()), 2);
+        assert_eq!(_nftoken.owner_of(5), Some(bob()));
+        assert_eq!(_nftoken.is_approved(5, dave()), true);
+        assert_eq!(_nftoken.is_approved(4, dave()), false);
+    }
+
+    #[test]
+    fn approvals_are_overwritten_revoked_and_cleared() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+        assert_eq!(_nftoken.approval_of(1), None);
+
+        // a new approval replaces the previous spender
+        assert_eq!(_nftoken.approval(bob(), 1, true), Ok(()));
+        assert_eq!(_nftoken.approval(charlie(), 1, true), Ok(()));
+        assert_eq!(_nftoken.approval_of(1), Some(charlie()));
+        assert_eq!(_nftoken.is_approved(1, bob()), false);
+
+        // there is nothing to revoke on a token without an approval, nor for another account
+        assert_eq!(_nftoken.approval(bob(), 2, false), Err(Error::NotFound));
+        assert_eq!(_nftoken.approval(bob(), 1, false), Err(Error::NotFound));
+        assert_eq!(_nftoken.approval_of(1), Some(charlie()));
+        assert_eq!(_nftoken.approval(charlie(), 1, false), Ok(()));
+        assert_eq!(_nftoken.approval_of(1), None);
+
+        // approvals do not survive a transfer, and the previous owner cannot give new ones
+        assert_eq!(_nftoken.approval(charlie(), 3, true), Ok(()));
+        assert_eq!(_nftoken.transfer(dave(), 3), Ok(()));
+        assert_eq!(_nftoken.approval_of(3), None);
+        assert_eq!(_nftoken.approvals_of(alice(), 0, 10), vec![]);
+        assert_eq!(_nftoken.approval(charlie(), 3, true), Err(Error::NotOwner));
+    }
+
+    #[test]
+    fn balance_of_batch_follows_the_given_order() {
+        let mut _nftoken = deploy!(Fixture::new(5).holders(2));
+        assert_eq!(_nftoken.balance_of_batch(vec![bob(), charlie(), alice(), bob()]), vec![2, 0, 3, 2]);
+        assert_eq!(_nftoken.balance_of_batch(vec![]), vec![]);
+    }
+
+    #[test]
+    fn nested_tokens_and_bundles_stay_apart() {
+        let mut _nftoken = deploy!(Fixture::new(5));
+        assert_eq!(_nftoken.nest(2, 1), Ok(()));
+        assert_eq!(_nftoken.create_bundle(vec![3, 4]), Ok(()));
+
+        // a nested token cannot join a bundle, and a bundled token cannot take children
+        assert_eq!(_nftoken.create_bundle(vec![2, 5]), Err(Error::TokenNested));
+        assert_eq!(_nftoken.nest(5, 3), Err(Error::TokenBundled));
+
+        // the parent still carries its child to the new owner
+        assert_eq!(_nftoken.transfer(bob(), 1), Ok(()));
+        assert_eq!(_nftoken.owner_of(2), Some(bob()));
+        assert_eq!(_nftoken.balance_of(alice()), 3);
+    }
+
+    #[test]
+    fn the_timelock_cannot_be_sidestepped() {
+        set_block_number(1);
+        let mut _nftoken = deploy!(Fixture::new(1));
+        assert_eq!(_nftoken.schedule(Action::SetTimelockDelay(10)), Ok(1));
+        assert_eq!(_nftoken.execute_scheduled(1), Ok(()));
+
+        // every admin setter has to be scheduled, including the ones that hand out admin rights
+        assert_eq!(_nftoken.set_signers(vec![alice()], 1), Err(Error::Timelocked));
+        assert_eq!(_nftoken.set_governance(0, 0), Err(Error::Timelocked));
+        assert_eq!(_nftoken.set_paused(true), Err(Error::Timelocked));
+        assert_eq!(_nftoken.set_max_per_holder(1), Err(Error::Timelocked));
+        assert_eq!(_nftoken.set_history_length(0), Err(Error::Timelocked));
+
+        // scheduled actions are checked as their setters would check them
+        assert_eq!(_nftoken.schedule(Action::SetSigners(vec![alice()], 2)), Err(Error::InvalidInput));
+        assert_eq!(_nftoken.schedule(Action::SetSigners(vec![alice()], 1)), Ok(2));
+        set_block_number(11);
+        assert_eq!(_nftoken.execute_scheduled(2), Ok(()));
+
+        // a single signer still only schedules, and the owner cannot cancel what the signers passed
+        assert_eq!(_nftoken.submit_admin_request(Action::TransferOwnership(bob())), Ok(1));
+        assert_eq!(_nftoken.owner(), alice());
+        assert_eq!(_nftoken.scheduled_of(3).unwrap().eta, 21);
+        assert_eq!(_nftoken.cancel_scheduled(3), Err(Error::Unauthorized));
+        set_block_number(21);
+        assert_eq!(_nftoken.execute_scheduled(3), Ok(()));
+        assert_eq!(_nftoken.owner(), bob());
+    }
+
+    #[test]
+    fn super_admin_changes_are_scheduled_under_a_timelock() {
+        set_block_number(1);
+        let mut _nftoken = deploy!(Fixture::new(1));
+        assert_eq!(_nftoken.set_super_admin(Some(charlie())), Ok(()));
+        assert_eq!(_nftoken.schedule(Action::SetTimelockDelay(10)), Ok(1));
+        assert_eq!(_nftoken.execute_scheduled(1), Ok(()));
+
+        // the super admin can still be revoked, through the timelock
+        assert_eq!(_nftoken.set_super_admin(None), Err(Error::Timelocked));
+        assert_eq!(_nftoken.schedule(Action::SetSuperAdmin(None)), Ok(2));
+        set_block_number(11);
+        assert_eq!(_nftoken.execute_scheduled(2), Ok(()));
+        assert_eq!(_nftoken.super_admin(), None);
+    }
+
+    #[test]
+    fn bundles_pass_the_transfer_hook_token_by_token() {
+        set_block_number(0);
+        let mut _nftoken = deploy!(Fixture::new(3));
+        assert_eq!(_nftoken.set_transfer_cooldown(5), Ok(()));
+        assert_eq!(_nftoken.nest(3, 2), Ok(()));
+        assert_eq!(_nftoken.create_bundle(vec![1, 2]), Ok(()));
+
+        // a bundle waits out the cooldown of its tokens just as they would on their own
+        assert_eq!(_nftoken.transfer_bundle(bob(), 1), Err(Error::TransferCooldown));
+
+        // and the cap counts the children nested in its tokens
+        set_block_number(5);
+        assert_eq!(_nftoken.set_max_per_holder(2), Ok(()));
+        assert_eq!(_nftoken.transfer_bundle(bob(), 1), Err(Error::HolderCapExceeded));
+        assert_eq!(_nftoken.set_max_per_holder(3), Ok(()));
+        assert_eq!(_nftoken.transfer_bundle(bob(), 1), Ok(()));
+        assert_eq!(_nftoken.balance_of(bob()), 3);
+    }
+
+    #[test]
+    fn recoveries_and_the_bridge_wait_out_a_pause() {
+        set_block_number(0);
+        let mut _nftoken = deploy!(Fixture::new(3));
+        assert_eq!(_nftoken.set_bridge(Some(eve())), Ok(()));
+        assert_eq!(_nftoken.approval(eve(), 1, true), Ok(()));
+        assert_eq!(_nftoken.approval(eve(), 2, true), Ok(()));
+        assert_eq!(_nftoken.set_backup(Some(bob()), 0), Ok(()));
+        set_caller(eve());
+        assert_eq!(_nftoken.bridge_out(1, 7, vec![0x1]), Ok(()));
+        assert_eq!(_nftoken.bridge_lock(2, 7, vec![0x1]), Ok(()));
+        set_caller(bob());
+        assert_eq!(_nftoken.announce_recovery(alice()), Ok(()));
+
+        set_caller(alice());
+        assert_eq!(_nftoken.set_paused(true), Ok(()));
+
+        // nothing is minted back, released or swept while paused
+        set_caller(eve());
+        assert_eq!(_nftoken.bridge_in(1, alice(), vec![0x2]), Err(Error::Paused));
+        assert_eq!(_nftoken.bridge_release(2, bob()), Err(Error::Paused));
+        assert_eq!(_nftoken.bridge_lock_of(2), Some((7, vec![0x1])));
+        set_caller(bob());
+        assert_eq!(_nftoken.recover_all(alice()), Err(Error::Paused));
+        assert_eq!(_nftoken.balance_of(alice()), 2);
+
+        // once resumed, the sweep leaves the token in custody behind
+        set_caller(alice());
+        assert_eq!(_nftoken.set_paused(false), Ok(()));
+        set_caller(bob());
+        assert_eq!(_nftoken.recover_all(alice()), Ok(()));
+        assert_eq!(_nftoken.owner_of(3), Some(bob()));
+        assert_eq!(_nftoken.owner_of(2), Some(alice()));
+    }
+
+    #[test]
+    fn owners_resolve_through_their_batch() {
+        set_block_number(0);
+        let mut _nftoken = deploy!(Fixture::new(3));
+        assert_eq!(_nftoken.mint(bob(), 1_000), Ok((4, 1_003)));
+        assert_eq!(_nftoken.mint(charlie(), 2), Ok((1_004, 1_005)));
+
+        // each token resolves to the receiver of its batch until it changes hands
+        assert_eq!(_nftoken.owner_of(3), Some(alice()));
+        assert_eq!(_nftoken.owner_of(4), Some(bob()));
+        assert_eq!(_nftoken.owner_of(1_003), Some(bob()));
+        assert_eq!(_nftoken.owner_of(1_004), Some(charlie()));
+        assert_eq!(_nftoken.provenance_of(1_003), Some((alice(), 0)));
+
+        set_caller(bob());
+        assert_eq!(_nftoken.transfer(alice(), 500), Ok(()));
+        assert_eq!(_nftoken.owner_of(499), Some(bob()));
+        assert_eq!(_nftoken.owner_of(500), Some(alice()));
+        assert_eq!(_nftoken.owner_of(501), Some(bob()));
+    }
+
+    #[test]
+    fn no_single_guardian_can_hold_a_recovery_to_their_address() {
+        set_block_number(0);
+        let mut _nftoken = deploy!(Fixture::new(1));
+        assert_eq!(_nftoken.set_guardians(vec![bob(), charlie(), dave()], 2, 5), Ok(()));
+
+        // bob backs his own address first, the others agree on eve
+        set_caller(bob());
+        assert_eq!(_nftoken.support_recovery(alice(), bob()), Ok(()));
+        assert_eq!(_nftoken.support_recovery(alice(), bob()), Err(Error::Duplicate));
+        set_caller(charlie());
+        assert_eq!(_nftoken.support_recovery(alice(), eve()), Ok(()));
+        set_caller(dave());
+        assert_eq!(_nftoken.support_recovery(alice(), eve()), Ok(()));
+        assert_eq!(_nftoken.recovery_of(alice()).unwrap().to, Some(eve()));
+
+        // a guardian changing their mind moves the recovery to whichever address a threshold
+        // now backs, and the delay starts over
+        set_block_number(3);
+        set_caller(charlie());
+        assert_eq!(_nftoken.support_recovery(alice(), bob()), Ok(()));
+        assert_eq!(_nftoken.recovery_of(alice()).unwrap().to, Some(bob()));
+        assert_eq!(_nftoken.recovery_of(alice()).unwrap().ready_at, Some(8));
+        assert_eq!(_nftoken.support_recovery(alice(), eve()), Ok(()));
+        assert_eq!(_nftoken.recovery_of(alice()).unwrap().to, Some(eve()));
+
+        set_block_number(8);
+        assert_eq!(_nftoken.complete_recovery(alice()), Ok(()));
+        assert_eq!(_nftoken.owner_of(1), Some(eve()));
+    }
+
+    #[test]
+    fn delegates_vote_with_the_power_they_held_at_the_snapshot() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+        _nftoken.mint(bob(), 1).unwrap();
+        assert_eq!(_nftoken.set_governance(10, 5_000), Ok(()));
+        assert_eq!(_nftoken.delegate(charlie()), Ok(()));
+
+        // charlie holds no tokens but votes with alice's
+        set_caller(bob());
+        assert_eq!(_nftoken.propose(Action::SetOperatorFilter(true)), Ok(1));
+        set_caller(alice());
+        assert_eq!(_nftoken.delegate(alice()), Ok(()));
+        assert!(_nftoken.vote(1, false).is_err());
+        set_caller(charlie());
+        assert_eq!(_nftoken.voting_power_at(charlie(), 1), 3);
+        assert_eq!(_nftoken.voting_power_of(charlie()), 0);
+        assert_eq!(_nftoken.vote(1, false), Ok(()));
+        assert_eq!(_nftoken.proposal_of(1).unwrap().votes_against, 3);
+
+        // no new proposal, from anyone, until the open one closes
+        set_caller(bob());
+        assert_eq!(_nftoken.propose(Action::SetPaused(true)), Err(Error::LimitReached));
+        set_caller(alice());
+        assert_eq!(_nftoken.propose(Action::SetPaused(true)), Err(Error::LimitReached));
+        set_block_number(11);
+        set_caller(bob());
+        assert_eq!(_nftoken.propose(Action::SetPaused(true)), Ok(2));
+
+        // supply checkpoints keep each snapshot's supply
+        set_caller(alice());
+        _nftoken.mint(alice(), 2).unwrap();
+        assert_eq!(_nftoken.total_supply_at(1), 4);
+        assert_eq!(_nftoken.total_supply_at(2), 4);
+        assert_eq!(_nftoken.total_minted(), 6);
+    }
+
+    #[test]
+    fn airdrops_are_claimed_against_a_merkle_root() {
+        use crate::hashing::merkle_parent;
+        let mut _nftoken = deploy!(Fixture::new(1));
+        let leaves = vec![
+            hash_of(&(0u64, bob(), 2 as TokenCount)),
+            hash_of(&(1u64, charlie(), 1 as TokenCount)),
+            hash_of(&(2u64, bob(), 3 as TokenCount)),
+        ];
+        let branch = merkle_parent(&leaves[0], &leaves[1]);
+        let root = merkle_parent(&branch, &leaves[2]);
+
+        // nothing can be claimed before a root is published, and only minters publish one
+        set_caller(bob());
+        assert_eq!(_nftoken.claim(vec![leaves[1], leaves[2]], 0, 2), Err(Error::NotFound));
+        assert_eq!(_nftoken.set_claim_root(Some(root)), Err(Error::Unauthorized));
+        set_caller(alice());
+        assert_eq!(_nftoken.set_claim_root(Some(root)), Ok(()));
+        assert_eq!(_nftoken.claim_root(), Some(root));
+
+        // a leaf only proves the account and quantity it was built from
+        set_caller(bob());
+        assert_eq!(_nftoken.claim(vec![leaves[1], leaves[2]], 0, 5), Err(Error::InvalidInput));
+        assert_eq!(_nftoken.claim(vec![leaves[0], leaves[2]], 1, 1), Err(Error::InvalidInput));
+        assert_eq!(_nftoken.claim(vec![leaves[1], leaves[2]], 0, 2), Ok((2, 3)));
+        assert_eq!(_nftoken.claim(vec![branch], 2, 3), Ok((4, 6)));
+        assert_eq!(_nftoken.balance_of(bob()), 5);
+
+        // each leaf is claimed once
+        assert_eq!(_nftoken.claim(vec![leaves[1], leaves[2]], 0, 2), Err(Error::Duplicate));
+        assert_eq!(_nftoken.is_claimed(0), true);
+        assert_eq!(_nftoken.is_claimed(1), false);
+
+        // a new root starts a new round
+        set_caller(alice());
+        assert_eq!(_nftoken.set_claim_root(Some(leaves[0])), Ok(()));
+        assert_eq!(_nftoken.is_claimed(0), false);
+        set_caller(bob());
+        assert_eq!(_nftoken.claim(vec![], 0, 2), Ok((7, 8)));
+    }
+
+    #[test]
+    fn rarity_scores_are_revealed_against_a_committed_root() {
+        use crate::hashing::merkle_parent;
+        let mut _nftoken = deploy!(Fixture::new(3));
+        let leaves = vec![hash_of(&(1u64, 10u32)), hash_of(&(2u64, 20u32))];
+        let root = merkle_parent(&leaves[0], &leaves[1]);
+
+        // the owner commits the root once
+        assert_eq!(_nftoken.reveal_rarity(1, 10, vec![leaves[1]]), Err(Error::NotFound));
+        set_caller(bob());
+        assert_eq!(_nftoken.commit_rarity_root(root), Err(Error::Unauthorized));
+        set_caller(alice());
+        assert_eq!(_nftoken.commit_rarity_root(root), Ok(()));
+        assert_eq!(_nftoken.commit_rarity_root(leaves[0]), Err(Error::Duplicate));
+        assert_eq!(_nftoken.rarity_root(), Some(root));
+
+        // anyone reveals a score, but only the one in the tree
+        set_caller(bob());
+        assert_eq!(_nftoken.reveal_rarity(2, 99, vec![leaves[0]]), Err(Error::InvalidInput));
+        assert_eq!(_nftoken.reveal_rarity(2, 20, vec![leaves[0]]), Ok(()));
+        assert_eq!(_nftoken.rarity_of(2), Some(20));
+        assert_eq!(_nftoken.reveal_rarity(2, 20, vec![leaves[0]]), Err(Error::Duplicate));
+        assert_eq!(_nftoken.rarity_of(1), None);
+
+        // the owner cannot get in ahead of a reveal with a score of their own
+        set_caller(alice());
+        assert_eq!(_nftoken.commit_rarity(vec![1], vec![99]), Err(Error::Duplicate));
+        assert_eq!(_nftoken.reveal_rarity(1, 10, vec![leaves[1]]), Ok(()));
+        assert_eq!(_nftoken.rarity_of(1), Some(10));
+    }
+
+    #[test]
+    fn scores_committed_before_the_root_are_not_revealed_again() {
+        use crate::hashing::merkle_parent;
+
+        let mut _nftoken = deploy!(Fixture::new(2));
+        let leaves = vec![hash_of(&(1u64, 10u32)), hash_of(&(2u64, 20u32))];
+        assert_eq!(_nftoken.commit_rarity(vec![1], vec![15]), Ok(()));
+        assert_eq!(_nftoken.commit_rarity_root(merkle_parent(&leaves[0], &leaves[1])), Ok(()));
+
+        // a token with a score keeps it, whatever the root says
+        assert_eq!(_nftoken.reveal_rarity(1, 10, vec![leaves[1]]), Err(Error::Duplicate));
+        assert_eq!(_nftoken.rarity_of(1), Some(15));
+    }
+
+    #[test]
+    fn gifts_go_to_whoever_presents_the_secret() {
+        let mut _nftoken = deploy!(Fixture::new(3));
+        let claim_hash = Hash::from(blake2_256(b"open sesame"));
+
+        // an escrowed gift cannot be moved by its sender
+        assert_eq!(_nftoken.create_gift(1, claim_hash), Ok(()));
+        assert_eq!(_nftoken.gift_of(1), Some(claim_hash));
+        assert_eq!(_nftoken.create_gift(2, claim_hash), Err(Error::Duplicate));
+        assert_eq!(_nftoken.transfer(bob(), 1), Err(Error::TokenInCustody));
+
+        // only the preimage of the claim hash claims the gift, once committed to in an earlier block
+        set_caller(bob());
+        assert_eq!(_nftoken.claim_gift(b"open barley".to_vec()), Err(Error::NotFound));
+        assert_eq!(_nftoken.cancel_gift(1), Err(Error::Unauthorized));
+        assert_eq!(_nftoken.claim_gift(b"open sesame".to_vec()), Err(Error::NotFound));
+        assert_eq!(_nftoken.commit_gift_claim(hash_of(&(b"open sesame".to_vec(), bob()))), Ok(()));
+        assert_eq!(_nftoken.claim_gift(b"open sesame".to_vec()), Err(Error::NotReady));
+        set_block_number(1);
+        assert_eq!(_nftoken.claim_gift(b"open sesame".to_vec()), Ok(()));
+        assert_eq!(_nftoken.owner_of(1), Some(bob()));
+        assert_eq!(_nftoken.gift_of(1), None);
+        assert_eq!(_nftoken.claim_gift(b"open sesame".to_vec()), Err(Error::NotFound));
+
+        // a cancelled gift is free to move again
+        set_caller(alice());
+        assert_eq!(_nftoken.create_gift(2, claim_hash), Ok(()));
+        assert_eq!(_nftoken.cancel_gift(2), Ok(()));
+        assert_eq!(_nftoken.transfer(charlie(), 2), Ok(()));
+        set_caller(bob());
+        assert_eq!(_nftoken.claim_gift(b"open sesame".to_vec()), Err(Error::NotFound));
+    }
+
+    #[test]
+    fn the_signing_domain_is_fixed_at_deploy() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+        let domain = _nftoken.domain_separator();
+        assert!(domain != Hash::from([0x0; 32]));
+
+        // the domain names the contract, not whoever administers it
+        assert_eq!(_nftoken.transfer_ownership(bob()), Ok(()));
+        assert_eq!(_nftoken.domain_separator(), domain);
+    }
+
+    #[test]
+    fn a_lone_holder_cannot_take_over_through_governance() {
+        let mut _nftoken = deploy!(Fixture::new(2).holders(2));
+
+        // with the deploy defaults nothing can be proposed
+        set_caller(bob());
+        assert_eq!(_nftoken.propose(Action::SetPaused(true)), Err(Error::NotReady));
+        set_caller(alice());
+        assert_eq!(_nftoken.set_governance(10, 0), Ok(()));
+        set_caller(bob());
+        assert_eq!(_nftoken.propose(Action::SetPaused(true)), Err(Error::NotReady));
+
+        // once governance is set up, roles and settings outside the holders' remit stay off the ballot
+        set_caller(alice());
+        assert_eq!(_nftoken.set_governance(10, 5_000), Ok(()));
+        set_caller(bob());
+        assert_eq!(_nftoken.propose(Action::TransferOwnership(bob())), Err(Error::Unauthorized));
+        assert_eq!(_nftoken.propose(Action::SetSuperAdmin(Some(bob()))), Err(Error::Unauthorized));
+        assert_eq!(_nftoken.propose(Action::SetSigners(vec![bob()], 1)), Err(Error::Unauthorized));
+        assert_eq!(_nftoken.propose(Action::SetGovernance(1, 1)), Err(Error::Unauthorized));
+        assert_eq!(_nftoken.proposal_of(1), None);
+        assert_eq!(_nftoken.owner(), alice());
+    }
+
+    #[test]
+    #[cfg(feature = "sr25519")]
+    fn sr25519_signatures_are_verified() {
+        use schnorrkel::{ExpansionMode, MiniSecretKey};
+
+        let _nftoken = deploy!(Fixture::new(1));
+        let keypair = MiniSecretKey::from_bytes(&[0x7; 32]).unwrap().expand_to_keypair(ExpansionMode::Ed25519);
+        let signer = AccountId::from(keypair.public.to_bytes());
+        let signature = keypair.sign_simple(b"substrate", b"payload").to_bytes().to_vec();
+
+        assert_eq!(_nftoken.verify_signature(signer, b"payload".to_vec(), signature.clone()), true);
+        assert_eq!(_nftoken.verify_signature(signer, b"another payload".to_vec(), signature.clone()), false);
+        assert_eq!(_nftoken.verify_signature(alice(), b"payload".to_vec(), signature.clone()), false);
+        assert_eq!(_nftoken.verify_signature(signer, b"payload".to_vec(), signature[..63].to_vec()), false);
+    }
+
+    #[test]
+    #[cfg(feature = "ecdsa")]
+    fn ecdsa_signatures_are_verified() {
+        use crate::hashing::blake2_256;
+        let _nftoken = deploy!(Fixture::new(1));
+        let secret = secp256k1::SecretKey::parse(&[0x9; 32]).unwrap();
+        let public = secp256k1::PublicKey::from_secret_key(&secret);
+        let signer = AccountId::from(blake2_256(&public.serialize_compressed()));
+        let (rs, recovery_id) = secp256k1::sign(&secp256k1::Message::parse(&blake2_256(b"payload")), &secret);
+        let mut signature = rs.serialize().to_vec();
+        signature.push(recovery_id.serialize());
+
+        assert_eq!(_nftoken.verify_signature(signer, b"payload".to_vec(), signature.clone()), true);
+        assert_eq!(_nftoken.verify_signature(signer, b"another payload".to_vec(), signature.clone()), false);
+        assert_eq!(_nftoken.verify_signature(alice(), b"payload".to_vec(), signature), false);
+    }
+
+    #[test]
+    #[cfg(feature = "sr25519")]
+    fn relayed_calls_run_as_the_signer_once() {
+        use schnorrkel::{ExpansionMode, Keypair, MiniSecretKey};
+
+        let mut _nftoken = deploy!(Fixture::new(2));
+        let keypair = MiniSecretKey::from_bytes(&[0x7; 32]).unwrap().expand_to_keypair(ExpansionMode::Ed25519);
+        let signer = AccountId::from(keypair.public.to_bytes());
+        assert_eq!(_nftoken.transfer(signer, 1), Ok(()));
+
+        let sign = |keypair: &Keypair, domain: Hash, call: &RelayedCall, nonce: u64, deadline: BlockNumber| {
+            let payload = (domain, signer, call.clone(), nonce, deadline).encode();
+            keypair.sign_simple(b"substrate", &payload).to_bytes().to_vec()
+        };
+        let domain = _nftoken.domain_separator();
+        let call = RelayedCall::Transfer(charlie(), 1);
+        let signature = sign(&keypair, domain, &call, 0, 10);
+
+        // bob relays the transfer and pays for it; the token leaves the signer, not bob
+        set_caller(bob());
+        assert_eq!(_nftoken.relay(signer, call.clone(), 0, 10, signature.clone()), Ok(()));
+        assert_eq!(_nftoken.owner_of(1), Some(charlie()));
+        assert_eq!(_nftoken.nonce_of(signer), 1);
+
+        // the same signature cannot be replayed
+        assert_eq!(_nftoken.relay(signer, call.clone(), 0, 10, signature.clone()), Err(Error::InvalidInput));
+        assert_eq!(_nftoken.relay(signer, call.clone(), 1, 10, signature), Err(Error::Unauthorized));
+
+        // a call is checked against the signer's ownership, and lapses at its deadline
+        let call = RelayedCall::Approval(bob(), 2, true);
+        let signature = sign(&keypair, domain, &call, 1, 10);
+        assert_eq!(_nftoken.relay(signer, call.clone(), 1, 10, signature), Err(Error::NotOwner));
+        assert_eq!(_nftoken.nonce_of(signer), 1);
+        let call = RelayedCall::SetApprovalForAll(bob(), true);
+        let signature = sign(&keypair, domain, &call, 1, 0);
+        set_block_number(1);
+        assert_eq!(_nftoken.relay(signer, call.clone(), 1, 0, signature), Err(Error::Expired));
+        let signature = sign(&keypair, domain, &call, 1, 10);
+        assert_eq!(_nftoken.relay(signer, call, 1, 10, signature), Ok(()));
+        assert_eq!(_nftoken.is_approved_for_all(signer, bob()), true);
+    }
+
+    #[test]
+    fn a_revealed_gift_secret_cannot_be_replayed_by_another_caller() {
+        let mut _nftoken = deploy!(Fixture::new(1));
+        assert_eq!(_nftoken.create_gift(1, Hash::from(blake2_256(b"open sesame"))), Ok(()));
+        set_caller(bob());
+        assert_eq!(_nftoken.commit_gift_claim(hash_of(&(b"open sesame".to_vec(), bob()))), Ok(()));
+        set_block_number(1);
+
+        // charlie sees bob's claim pending and races it with the same secret
+        set_caller(charlie());
+        assert_eq!(_nftoken.claim_gift(b"open sesame".to_vec()), Err(Error::NotFound));
+        // nor does copying bob's commitment help, it names bob as the claimer
+        assert_eq!(_nftoken.commit_gift_claim(hash_of(&(b"open sesame".to_vec(), bob()))), Err(Error::Duplicate));
+        assert_eq!(_nftoken.commit_gift_claim(hash_of(&(b"open sesame".to_vec(), charlie()))), Ok(()));
+        assert_eq!(_nftoken.claim_gift(b"open sesame".to_vec()), Err(Error::NotReady));
+
+        set_caller(bob());
+        assert_eq!(_nftoken.claim_gift(b"open sesame".to_vec()), Ok(()));
+        assert_eq!(_nftoken.owner_of(1), Some(bob()));
+    }
+
+    #[test]
+    fn admin_transfers_respect_the_holder_cap_but_not_locks() {
+        let mut _nftoken = deploy!(Fixture::new(2));
+        let reason = Hash::from([0x7; 32]);
+        assert_eq!(_nftoken.mint_locked(alice(), 1, 100), Ok((3, 3)));
+        assert_eq!(_nftoken.transfer(bob(), 1), Ok(()));
+        assert_eq!(_nftoken.set_max_per_holder(1), Ok(()));
+        assert_eq!(_nftoken.set_super_admin(Some(charlie())), Ok(()));
+        assert_eq!(_nftoken.set_paused(true), Ok(()));
+
+        // bob already holds as many tokens as the cap allows
+        set_caller(charlie());
+        assert_eq!(_nftoken.admin_transfer(2, bob(), reason), Err(Error::HolderCapExceeded));
+        assert_eq!(_nftoken.owner_of(2), Some(alice()));
+
+        // a time lock does not hold back a forced transfer
+        assert_eq!(_nftoken.admin_transfer(1, dave(), reason), Ok(()));
+        assert_eq!(_nftoken.admin_transfer(3, bob(), reason), Ok(()));
+        assert_eq!(_nftoken.owner_of(3), Some(bob()));
+    }
+
+    #[test]
+    fn subscriptions_are_renewed_by_their_holder_or_an_approved_account() {
+        let mut _nftoken = deploy!(Fixture::new(1).approve(1, charlie()));
+        assert_eq!(_nftoken.set_expiry(1, 0), Ok(()));
+        assert_eq!(_nftoken.set_renewal_period(30), Ok(()));
+
+        set_caller(bob());
+        assert_eq!(_nftoken.renew(1), Err(Error::NotApproved));
+        assert_eq!(_nftoken.renew(2), Err(Error::TokenNotFound));
+        assert_eq!(_nftoken.expires_at(1), 0);
+
+        set_caller(charlie());
+        assert_eq!(_nftoken.renew(1), Ok(()));
+        assert_eq!(_nftoken.expires_at(1), 30);
     }
 }
@@ -0,0 +1,38 @@
+// Copyright 2017-2019 JKRB Investments Limited.
+//
+// You should have received a copy of the GNU General Public License
+// along with this file.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Guardians that can move a holder's tokens to a recovery address
+
+use ink_core::{env::{AccountId, BlockNumber}, memory::vec::Vec};
+use parity_codec::{Decode, Encode};
+
+/// Guardians a holder trusts to recover their tokens
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct GuardianSet {
+    /// Accounts that can support a recovery
+    pub guardians: Vec<AccountId>,
+    /// Guardians that have to support a recovery before it starts
+    pub threshold: u32,
+    /// Blocks the holder has to veto a recovery once it starts
+    pub delay: BlockNumber,
+}
+
+/// A recovery of a holder's tokens, supported by some of their guardians
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct RecoveryRequest {
+    /// (guardian, recovery address) each supporting guardian backs; a guardian backs one address at a time
+    pub supporters: Vec<(AccountId, AccountId)>,
+    /// Account the tokens move to, once enough guardians back the same one
+    pub to: Option<AccountId>,
+    /// First block the recovery can complete in, once enough guardians back the same address
+    pub ready_at: Option<BlockNumber>,
+}
+
+impl RecoveryRequest {
+    /// Number of guardians backing a recovery address
+    pub fn support_for(&self, to: &AccountId) -> u32 {
+        self.supporters.iter().filter(|(_, backed)| backed == to).count() as u32
+    }
+}
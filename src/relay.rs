@@ -0,0 +1,20 @@
+// Copyright 2017-2019 JKRB Investments Limited.
+//
+// You should have received a copy of the GNU General Public License
+// along with this file.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Calls a relayer submits on behalf of the account that signed them
+
+use ink_core::env::AccountId;
+use parity_codec::{Decode, Encode};
+
+/// A call an account signs off-chain for a relayer to submit
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum RelayedCall {
+    /// transfer(to, token_id)
+    Transfer(AccountId, u64),
+    /// approval(to, token_id, approved)
+    Approval(AccountId, u64, bool),
+    /// set_approval_for_all(operator, approved)
+    SetApprovalForAll(AccountId, bool),
+}
@@ -0,0 +1,56 @@
+// Copyright 2017-2019 JKRB Investments Limited.
+//
+// You should have received a copy of the GNU General Public License
+// along with this file.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Checks of signatures made off-chain over payloads the contract acts on. Each scheme
+//! sits behind the feature of the same name, so a deployment accepts only the schemes
+//! it is built with
+
+#[cfg(feature = "ecdsa")]
+use crate::hashing::blake2_256;
+use ink_core::env::AccountId;
+#[cfg(feature = "sr25519")]
+use parity_codec::Encode;
+
+/// Signing context of sr25519 signatures made by substrate wallets
+#[cfg(feature = "sr25519")]
+const SR25519_CONTEXT: &[u8] = b"substrate";
+
+/// Whether a signature by an account over a message is valid under one of the accepted
+/// schemes, told apart by length: 64 bytes for sr25519, 65 for recoverable ecdsa
+pub(crate) fn verify(signer: &AccountId, message: &[u8], signature: &[u8]) -> bool {
+    match signature.len() {
+        #[cfg(feature = "sr25519")]
+        64 => verify_sr25519(signer, message, signature),
+        #[cfg(feature = "ecdsa")]
+        65 => verify_ecdsa(signer, message, signature),
+        _ => false,
+    }
+}
+
+/// An sr25519 signature, checked with the account as the public key
+#[cfg(feature = "sr25519")]
+fn verify_sr25519(signer: &AccountId, message: &[u8], signature: &[u8]) -> bool {
+    let public = schnorrkel::PublicKey::from_bytes(&signer.encode());
+    let signature = schnorrkel::Signature::from_bytes(signature);
+    match (public, signature) {
+        (Ok(public), Ok(signature)) => public.verify_simple(SR25519_CONTEXT, message, &signature).is_ok(),
+        _ => false,
+    }
+}
+
+/// A recoverable ecdsa signature over the message's BLAKE2b-256 hash; the account is the
+/// BLAKE2b-256 hash of the compressed public key it recovers to
+#[cfg(feature = "ecdsa")]
+fn verify_ecdsa(signer: &AccountId, message: &[u8], signature: &[u8]) -> bool {
+    let mut rs = [0u8; 64];
+    rs.copy_from_slice(&signature[..64]);
+    let digest = secp256k1::Message::parse(&blake2_256(message));
+    let recovered = secp256k1::RecoveryId::parse(signature[64])
+        .and_then(|recovery_id| secp256k1::recover(&digest, &secp256k1::Signature::parse(&rs), &recovery_id));
+    match recovered {
+        Ok(public) => AccountId::from(blake2_256(&public.serialize_compressed())) == *signer,
+        Err(_) => false,
+    }
+}
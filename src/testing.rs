@@ -0,0 +1,99 @@
+// Copyright 2017-2019 JKRB Investments Limited.
+//
+// You should have received a copy of the GNU General Public License
+// along with this file.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helpers for off-chain tests: switching the caller and block between calls,
+//! and the accounts the tests act as
+
+use ink_core::{
+    env::{self, AccountId, BlockNumber, DefaultSrmlTypes},
+    memory::vec::Vec,
+};
+
+use crate::types::TokenCount;
+
+type Types = DefaultSrmlTypes;
+
+/// An account whose 32 bytes are all the given byte
+pub fn account(byte: u8) -> AccountId {
+    AccountId::from([byte; 32])
+}
+
+/// The account tests deploy the contract as, and so its owner
+pub fn alice() -> AccountId {
+    account(0x1)
+}
+
+/// A second holder, typically the receiver of transfers
+pub fn bob() -> AccountId {
+    account(0x2)
+}
+
+/// A third party, typically an approved spender or operator
+pub fn charlie() -> AccountId {
+    account(0x3)
+}
+
+/// A fourth account, for guardians, users and backups
+pub fn dave() -> AccountId {
+    account(0x4)
+}
+
+/// A fifth account, typically the bridge
+pub fn eve() -> AccountId {
+    account(0x5)
+}
+
+/// Makes the following calls come from the given account
+pub fn set_caller(caller: AccountId) {
+    env::test::set_caller::<Types>(caller);
+}
+
+/// Makes the following calls run at the given block
+pub fn set_block_number(block: BlockNumber) {
+    env::test::set_block_number::<Types>(block);
+}
+
+/// Runs a call as the given account, leaving it as the caller afterwards
+pub fn as_caller<R>(caller: AccountId, call: impl FnOnce() -> R) -> R {
+    set_caller(caller);
+    call()
+}
+
+/// Describes the state a test starts from: how many tokens exist, how many
+/// accounts hold them and which tokens have an approved spender. Token ids are
+/// dealt out in turn, so token 1 goes to alice, token 2 to bob and so on
+pub struct Fixture {
+    /// Tokens minted at deploy
+    pub tokens: TokenCount,
+    /// Accounts the tokens are spread across, starting at alice
+    pub holders: u8,
+    /// Approvals given by the holder of each token, as (token_id, spender)
+    pub approvals: Vec<(u64, AccountId)>,
+}
+
+impl Fixture {
+    /// A fixture of the given number of tokens, all held by alice
+    pub fn new(tokens: TokenCount) -> Self {
+        Fixture { tokens: tokens, holders: 1, approvals: Vec::new() }
+    }
+
+    /// Spreads the tokens across the given number of accounts, at least one
+    pub fn holders(mut self, holders: u8) -> Self {
+        assert!(holders > 0, "a fixture needs at least one holder");
+        self.holders = holders;
+        self
+    }
+
+    /// Has the holder of a token approve a spender for it
+    pub fn approve(mut self, token_id: u64, spender: AccountId) -> Self {
+        self.approvals.push((token_id, spender));
+        self
+    }
+
+    /// The account a token is dealt to
+    pub fn holder_of(&self, token_id: u64) -> AccountId {
+        account(1 + ((token_id - 1) % self.holders as u64) as u8)
+    }
+}
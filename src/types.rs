@@ -0,0 +1,57 @@
+// Copyright 2017-2019 JKRB Investments Limited.
+//
+// You should have received a copy of the GNU General Public License
+// along with this file.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types and helpers shared by the contract and its extensions
+
+use crate::errors::Error;
+use ink_core::{env::AccountId, storage};
+
+/// Number of tokens, used for balances, supply counters and mint sizes
+pub type TokenCount = u64;
+
+/// Whether an account is the default, all-zero account
+pub(crate) fn is_zero_account(account: &AccountId) -> bool {
+    *account == AccountId::from([0x0; 32])
+}
+
+/// Resolves a value at a snapshot from `count` (snapshot_id, value) checkpoints read through
+/// `checkpoint`, ordered by snapshot_id, where each checkpoint holds the value when that
+/// snapshot was taken. Values that have not changed since the snapshot are still current.
+pub(crate) fn snapshot_value<T: Copy>(count: u64, checkpoint: impl Fn(u64) -> Option<(u64, T)>, snapshot_id: u64, current: T) -> T {
+    // the first checkpoint taken at or after the snapshot holds the value it saw
+    let mut low = 0;
+    let mut high = count;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let before = checkpoint(mid).map(|(id, _)| id < snapshot_id).unwrap_or(false);
+        if before == true {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    if low == count {
+        return current;
+    }
+    checkpoint(low).map(|(_, value)| value).unwrap_or(current)
+}
+
+/// Records an account's value before it changes in the current snapshot, unless the snapshot
+/// already has a checkpoint; only the account's last checkpoint is read
+pub(crate) fn push_checkpoint(
+    checkpoints: &mut storage::HashMap<(AccountId, u32), (u64, TokenCount)>,
+    counts: &mut storage::HashMap<AccountId, u32>,
+    account: AccountId,
+    snapshot_id: u64,
+    value: TokenCount,
+) -> Result<(), Error> {
+    let count = *counts.get(&account).unwrap_or(&0);
+    let checkpointed = count > 0 && checkpoints.get(&(account, count - 1)).map(|(id, _)| *id >= snapshot_id).unwrap_or(false);
+    if checkpointed == false {
+        checkpoints.insert((account, count), (snapshot_id, value));
+        counts.insert(account, count.checked_add(1).ok_or(Error::Overflow)?);
+    }
+    Ok(())
+}
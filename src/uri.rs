@@ -0,0 +1,45 @@
+// Copyright 2017-2019 JKRB Investments Limited.
+//
+// You should have received a copy of the GNU General Public License
+// along with this file.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Format checks for metadata URIs
+
+use ink_core::memory::vec::Vec;
+
+const IPFS_PREFIX: &[u8] = b"ipfs://";
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Whether a URI has the form `ipfs://<CIDv0|CIDv1>`, optionally followed by a path
+pub(crate) fn is_ipfs_uri(uri: &[u8]) -> bool {
+    if !uri.starts_with(IPFS_PREFIX) {
+        return false;
+    }
+    let rest = &uri[IPFS_PREFIX.len()..];
+    let cid = rest.split(|b| *b == b'/').next().unwrap_or(&[]);
+    is_cid_v0(cid) || is_cid_v1(cid)
+}
+
+/// Whether a URI starts with `<scheme>://` and names something after it
+pub(crate) fn has_scheme(uri: &[u8], scheme: &[u8]) -> bool {
+    let prefix_len = scheme.len() + 3;
+    uri.len() > prefix_len
+        && uri.starts_with(scheme)
+        && &uri[scheme.len()..prefix_len] == b"://"
+}
+
+/// Whether a list of URI schemes names every scheme; an empty scheme would match any `://` URI
+pub(crate) fn is_valid_schemes(schemes: &[Vec<u8>]) -> bool {
+    schemes.iter().all(|scheme| !scheme.is_empty())
+}
+
+/// A CIDv0 is a base58btc sha2-256 multihash: 46 characters starting with `Qm`
+fn is_cid_v0(cid: &[u8]) -> bool {
+    cid.len() == 46 && cid.starts_with(b"Qm") && cid.iter().all(|b| BASE58_ALPHABET.contains(b))
+}
+
+/// A CIDv1 in its default base32 encoding: the `b` multibase prefix and at least 58 characters
+fn is_cid_v1(cid: &[u8]) -> bool {
+    cid.len() >= 59 && cid[0] == b'b' && cid[1..].iter().all(|b| BASE32_ALPHABET.contains(b))
+}